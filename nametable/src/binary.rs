@@ -0,0 +1,397 @@
+use core::convert::TryFrom;
+use core::error;
+use core::fmt;
+use core::mem;
+use core::ops::{Index, Range};
+use core::slice;
+use core::str;
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use nametable::{fnv1a_bytes, NameTable, NameTableIdx, FNV_OFFSET_BASIS, NAME_HASH_VERSION};
+use lookup::{HashedNames, SplitHashEntries};
+use perfect_hash::{PerfectHash, PerfectHashBuf};
+
+/// The first eight bytes of every binary name table.
+pub const BINARY_MAGIC: [u8; 8] = *b"NAMETABL";
+
+/// Version of the binary layout described on `MappedNameTable`.
+pub const BINARY_FORMAT_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = 80;
+const SECTION_ALIGN: usize = 8;
+
+const LITTLE_ENDIAN: u8 = 1;
+const BIG_ENDIAN: u8 = 2;
+
+const FORMAT_VERSION_AT: usize = 8;
+const HASH_VERSION_AT: usize = 12;
+const ENDIANNESS_AT: usize = 16;
+const WORD_SIZE_AT: usize = 17;
+const CHECKSUM_AT: usize = 24;
+const INITIAL_AT: usize = 32;
+const COUNT_AT: usize = 40;
+const NAMES_LEN_AT: usize = 48;
+const SEED_AT: usize = 56;
+const DISPLACEMENT_COUNT_AT: usize = 64;
+const MAP_COUNT_AT: usize = 72;
+
+/// The reason a byte slice could not be loaded as a `MappedNameTable`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// The data ends before the header or one of its sections does.
+    Truncated,
+    /// The data does not start with `BINARY_MAGIC`.
+    BadMagic,
+    /// The data uses a layout version other than `BINARY_FORMAT_VERSION`.
+    UnsupportedVersion(u32),
+    /// The hashes were computed with a `name_hash` version other than
+    /// `NAME_HASH_VERSION`.
+    HashVersionMismatch(u32),
+    /// The data was written on a target with a different byte order or word
+    /// size, so it cannot be used in place.
+    ForeignLayout { endianness: u8, word_size: u8 },
+    /// The data does not start on an eight byte boundary.
+    Misaligned,
+    /// The sections do not match the checksum in the header.
+    ChecksumMismatch,
+    /// The supplied parent chain ends at `expected`, but the layer starts at
+    /// `found`.
+    InitialMismatch { expected: usize, found: usize },
+    /// The checksum matches, but the sections are inconsistent.
+    Corrupt(&'static str),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FormatError::Truncated => f.write_str("binary name table is truncated"),
+            FormatError::BadMagic => f.write_str("data is not a binary name table"),
+            FormatError::UnsupportedVersion(version) => {
+                write!(f,
+                       "binary name table has format version {}, expected {}",
+                       version,
+                       BINARY_FORMAT_VERSION)
+            }
+            FormatError::HashVersionMismatch(version) => {
+                write!(f,
+                       "binary name table was hashed with name_hash version {}, expected {}",
+                       version,
+                       NAME_HASH_VERSION)
+            }
+            FormatError::ForeignLayout { endianness, word_size } => {
+                write!(f,
+                       "binary name table was written for byte order {} and {}-byte words",
+                       endianness,
+                       word_size)
+            }
+            FormatError::Misaligned => {
+                f.write_str("binary name table data is not aligned to 8 bytes")
+            }
+            FormatError::ChecksumMismatch => f.write_str("binary name table checksum mismatch"),
+            FormatError::InitialMismatch { expected, found } => {
+                write!(f,
+                       "binary name table starts at index {}, but its parent ends at index {}",
+                       found,
+                       expected)
+            }
+            FormatError::Corrupt(reason) => write!(f, "binary name table is corrupt: {}", reason),
+        }
+    }
+}
+
+impl error::Error for FormatError {}
+
+/// A hashed table layer that borrows its names, offsets and perfect hash from a
+/// byte slice in the binary format, typically a memory-mapped file. Loading
+/// validates the data but copies none of it; lookups go through the same code
+/// as `StaticHashedNameTable`.
+///
+/// The format stores integers in the byte order and word size of the target
+/// that wrote it, so the sections can be used in place. It starts with an
+/// 80-byte header:
+///
+/// | offset | size | field |
+/// |--------|------|-------|
+/// | 0  | 8 | `BINARY_MAGIC` |
+/// | 8  | 4 | `BINARY_FORMAT_VERSION` |
+/// | 12 | 4 | `NAME_HASH_VERSION` of the hashes |
+/// | 16 | 1 | byte order: 1 little endian, 2 big endian |
+/// | 17 | 1 | `size_of::<usize>()` |
+/// | 24 | 8 | FNV-1a 64 checksum of everything after the header |
+/// | 32 | 8 | index of the layer's first name |
+/// | 40 | 8 | number of names |
+/// | 48 | 8 | length of the names blob in bytes |
+/// | 56 | 8 | perfect hash seed |
+/// | 64 | 8 | number of perfect hash displacements |
+/// | 72 | 8 | length of the perfect hash map |
+///
+/// The header is followed by these sections, each padded to a multiple of
+/// eight bytes: the UTF-8 names blob, the name offsets (`usize`, one more than
+/// the number of names), the displacements (`[u32; 2]`) and the map (`usize`).
+pub struct MappedNameTable<'a> {
+    initial_idx: usize,
+    names: &'a str,
+    name_offsets: &'a [usize],
+    perfect_hash: Option<PerfectHash<'a>>,
+    parent: Option<Box<dyn NameTable + Send + Sync + 'a>>,
+}
+
+impl<'a> MappedNameTable<'a> {
+    /// Loads a layer without a parent, which must start at index 0.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, FormatError> {
+        MappedNameTable::load(bytes, 0, None)
+    }
+
+    /// Loads a layer on top of `parent`, which must end exactly where the
+    /// layer starts.
+    pub fn from_bytes_upon<ParentTableType>(bytes: &'a [u8],
+                                            parent: ParentTableType)
+                                            -> Result<Self, FormatError>
+        where ParentTableType: 'a + NameTable + Send + Sync
+    {
+        let expected = parent.initial_local() + parent.len_local();
+        MappedNameTable::load(bytes, expected, Some(Box::new(parent)))
+    }
+
+    /// Writes the local layer of `table` in the binary format.
+    ///
+    /// The buffer can be written to a file as-is. A `Vec<u8>` is not
+    /// guaranteed to be aligned, so load from a memory map or an aligned copy
+    /// rather than from the returned buffer itself.
+    pub fn encode_layer<T: NameTable + ?Sized>(table: &T) -> Vec<u8> {
+        let names: Vec<&str> = (0..table.len_local()).map(|idx| table.at_local(idx)).collect();
+        let mut offsets = vec![0usize];
+        let mut names_len = 0;
+        for name in names.iter() {
+            names_len += name.len();
+            offsets.push(names_len);
+        }
+        let perfect_hash = PerfectHashBuf::build(&names);
+
+        let mut out = vec![0u8; HEADER_LEN];
+        for name in names.iter() {
+            out.extend_from_slice(name.as_bytes());
+        }
+        pad_section(&mut out);
+        for &offset in offsets.iter() {
+            out.extend_from_slice(&offset.to_ne_bytes());
+        }
+        pad_section(&mut out);
+        for displacement in perfect_hash.displacements().iter() {
+            out.extend_from_slice(&displacement[0].to_ne_bytes());
+            out.extend_from_slice(&displacement[1].to_ne_bytes());
+        }
+        pad_section(&mut out);
+        for &idx in perfect_hash.map().iter() {
+            out.extend_from_slice(&idx.to_ne_bytes());
+        }
+        pad_section(&mut out);
+
+        let checksum = fnv1a_bytes(FNV_OFFSET_BASIS, &out[HEADER_LEN..]);
+        out[..8].copy_from_slice(&BINARY_MAGIC);
+        write_at(&mut out, FORMAT_VERSION_AT, &BINARY_FORMAT_VERSION.to_ne_bytes());
+        write_at(&mut out, HASH_VERSION_AT, &NAME_HASH_VERSION.to_ne_bytes());
+        out[ENDIANNESS_AT] = native_endianness();
+        out[WORD_SIZE_AT] = mem::size_of::<usize>() as u8;
+        write_at(&mut out, CHECKSUM_AT, &checksum.to_ne_bytes());
+        let counts = [(INITIAL_AT, table.initial_local()),
+                      (COUNT_AT, names.len()),
+                      (NAMES_LEN_AT, names_len),
+                      (DISPLACEMENT_COUNT_AT, perfect_hash.displacements().len()),
+                      (MAP_COUNT_AT, perfect_hash.map().len())];
+        for &(at, value) in counts.iter() {
+            write_at(&mut out, at, &(value as u64).to_ne_bytes());
+        }
+        write_at(&mut out, SEED_AT, &perfect_hash.seed().to_ne_bytes());
+        out
+    }
+
+    fn load(bytes: &'a [u8],
+            expected_initial: usize,
+            parent: Option<Box<dyn NameTable + Send + Sync + 'a>>)
+            -> Result<Self, FormatError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(FormatError::Truncated);
+        }
+        if bytes[..8] != BINARY_MAGIC {
+            return Err(FormatError::BadMagic);
+        }
+        let endianness = bytes[ENDIANNESS_AT];
+        let word_size = bytes[WORD_SIZE_AT];
+        if endianness != native_endianness() || word_size as usize != mem::size_of::<usize>() {
+            return Err(FormatError::ForeignLayout {
+                endianness,
+                word_size,
+            });
+        }
+        let format_version = read_u32(bytes, FORMAT_VERSION_AT);
+        if format_version != BINARY_FORMAT_VERSION {
+            return Err(FormatError::UnsupportedVersion(format_version));
+        }
+        let hash_version = read_u32(bytes, HASH_VERSION_AT);
+        if hash_version != NAME_HASH_VERSION {
+            return Err(FormatError::HashVersionMismatch(hash_version));
+        }
+        if !(bytes.as_ptr() as usize).is_multiple_of(SECTION_ALIGN) {
+            return Err(FormatError::Misaligned);
+        }
+
+        let initial_idx = read_len(bytes, INITIAL_AT)?;
+        if initial_idx != expected_initial {
+            return Err(FormatError::InitialMismatch {
+                expected: expected_initial,
+                found: initial_idx,
+            });
+        }
+        let count = read_len(bytes, COUNT_AT)?;
+        let displacement_count = read_len(bytes, DISPLACEMENT_COUNT_AT)?;
+        let map_count = read_len(bytes, MAP_COUNT_AT)?;
+
+        let word = mem::size_of::<usize>();
+        let mut end = HEADER_LEN;
+        let names_range = next_section(&mut end, Some(read_len(bytes, NAMES_LEN_AT)?))?;
+        let offsets_len = count.checked_add(1).and_then(|offsets| offsets.checked_mul(word));
+        let offsets_range = next_section(&mut end, offsets_len)?;
+        let displacements_range = next_section(&mut end, displacement_count.checked_mul(8))?;
+        let map_range = next_section(&mut end, map_count.checked_mul(word))?;
+        if end > bytes.len() {
+            return Err(FormatError::Truncated);
+        }
+        if fnv1a_bytes(FNV_OFFSET_BASIS, &bytes[HEADER_LEN..end]) != read_u64(bytes, CHECKSUM_AT) {
+            return Err(FormatError::ChecksumMismatch);
+        }
+
+        let names = str::from_utf8(&bytes[names_range])
+            .map_err(|_| FormatError::Corrupt("names are not valid UTF-8"))?;
+        let name_offsets: &[usize] = cast_section(&bytes[offsets_range]);
+        let displacements: &[[u32; 2]] = cast_section(&bytes[displacements_range]);
+        let map: &[usize] = cast_section(&bytes[map_range]);
+
+        if name_offsets[0] != 0 || name_offsets[count] != names.len() ||
+           name_offsets.windows(2).any(|pair| pair[0] > pair[1]) ||
+           !name_offsets.iter().all(|&offset| names.is_char_boundary(offset)) {
+            return Err(FormatError::Corrupt("name offsets do not delimit the names"));
+        }
+        if map.iter().any(|&idx| idx >= count) || (map_count != 0 && displacement_count == 0) {
+            return Err(FormatError::Corrupt("perfect hash refers to a missing name"));
+        }
+
+        let perfect_hash = if map_count != 0 {
+            Some(PerfectHash {
+                seed: read_u64(bytes, SEED_AT),
+                displacements,
+                map,
+            })
+        } else {
+            None
+        };
+        Ok(MappedNameTable {
+            initial_idx,
+            names,
+            name_offsets,
+            perfect_hash,
+            parent,
+        })
+    }
+
+    fn local<'s>(&'s self) -> HashedNames<'s> {
+        HashedNames {
+            names: self.names,
+            offsets: self.name_offsets,
+            hashes: SplitHashEntries::EMPTY,
+            perfect_hash: self.perfect_hash,
+        }
+    }
+}
+
+impl<'t> NameTable for MappedNameTable<'t> {
+    fn initial_local(&self) -> usize {
+        self.initial_idx
+    }
+    fn len_local(&self) -> usize {
+        self.local().len()
+    }
+    fn at_local(&self, idx: usize) -> &str {
+        self.local().at(idx)
+    }
+    fn parent(&self) -> Option<&dyn NameTable> {
+        self.parent.as_ref().map(|boxed| boxed.as_ref() as &dyn NameTable)
+    }
+
+    fn find_local(&self, name: &str) -> Option<usize> {
+        self.local().find(name)
+    }
+}
+
+impl<'t, T: NameTableIdx> Index<T> for MappedNameTable<'t> {
+    type Output = str;
+
+    fn index(&self, _index: T) -> &str {
+        self.at(_index.to_index())
+    }
+}
+
+fn native_endianness() -> u8 {
+    if cfg!(target_endian = "little") {
+        LITTLE_ENDIAN
+    } else {
+        BIG_ENDIAN
+    }
+}
+
+fn pad_section(out: &mut Vec<u8>) {
+    while !out.len().is_multiple_of(SECTION_ALIGN) {
+        out.push(0);
+    }
+}
+
+fn write_at(out: &mut [u8], at: usize, value: &[u8]) {
+    out[at..at + value.len()].copy_from_slice(value);
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&bytes[at..at + 4]);
+    u32::from_ne_bytes(buf)
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    let mut buf = [0u8; 8];
+    buf.copy_from_slice(&bytes[at..at + 8]);
+    u64::from_ne_bytes(buf)
+}
+
+fn read_len(bytes: &[u8], at: usize) -> Result<usize, FormatError> {
+    usize::try_from(read_u64(bytes, at)).map_err(|_| FormatError::Truncated)
+}
+
+/// Claims the next section of `len` bytes, starting at `*end`, and moves
+/// `*end` past its padding. Lengths that overflow cannot fit in any slice.
+fn next_section(end: &mut usize, len: Option<usize>) -> Result<Range<usize>, FormatError> {
+    let start = *end;
+    let section_end = len.and_then(|len| start.checked_add(len)).ok_or(FormatError::Truncated)?;
+    *end = section_end
+        .checked_add(SECTION_ALIGN - 1)
+        .ok_or(FormatError::Truncated)? / SECTION_ALIGN * SECTION_ALIGN;
+    Ok(start..section_end)
+}
+
+/// Integer types that every bit pattern is a valid value of.
+///
+/// # Safety
+///
+/// Implementors must have no padding and no invalid bit patterns.
+unsafe trait Plain: Copy {}
+
+unsafe impl Plain for u64 {}
+unsafe impl Plain for usize {}
+unsafe impl Plain for [u32; 2] {}
+
+/// Reinterprets a section as a slice of `T`. Sections start at a multiple of
+/// eight bytes from a base that `load` checked to be aligned to eight bytes,
+/// which satisfies the alignment of every `Plain` type.
+fn cast_section<T: Plain>(bytes: &[u8]) -> &[T] {
+    assert!((bytes.as_ptr() as usize).is_multiple_of(mem::align_of::<T>()));
+    unsafe { slice::from_raw_parts(bytes.as_ptr() as *const T, bytes.len() / mem::size_of::<T>()) }
+}
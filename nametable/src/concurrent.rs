@@ -0,0 +1,145 @@
+use core::ops::Index;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::boxed::Box;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::thread;
+use nametable::{name_hash, NameTable, NameTableIdx};
+
+const SHARD_COUNT: usize = 32;
+const FIRST_CHUNK_BITS: u32 = 6;
+const FIRST_CHUNK_LEN: usize = 1 << FIRST_CHUNK_BITS;
+const CHUNK_COUNT: usize = (usize::BITS - FIRST_CHUNK_BITS) as usize;
+
+type Slot = OnceLock<Arc<str>>;
+type Shard = RwLock<HashMap<Arc<str>, usize>>;
+
+/// A dynamic table that any number of threads can intern into through a
+/// shared reference. Interning the same string from any thread returns the
+/// same index.
+///
+/// Names live in append-only chunks that never move once allocated, so `at`
+/// reads them without taking a lock or waiting. Only `at` is lock-free:
+/// `find` takes a read lock on one of several independently locked shards,
+/// picked by the name's hash, and `intern` takes its write lock to add a new
+/// name, so threads interning different names rarely contend.
+pub struct ConcurrentNameTable {
+    initial_idx: usize,
+    // Indices handed out to `intern`, some of which may not be stored yet.
+    reserved: AtomicUsize,
+    // Indices whose names are stored, which is what readers see.
+    len: AtomicUsize,
+    chunks: Box<[OnceLock<Box<[Slot]>>]>,
+    shards: Box<[Shard]>,
+    parent: Option<Box<dyn NameTable + Send + Sync>>,
+}
+
+impl Default for ConcurrentNameTable {
+    fn default() -> Self {
+        ConcurrentNameTable::new()
+    }
+}
+
+impl ConcurrentNameTable {
+    pub fn new() -> Self {
+        ConcurrentNameTable::with_parent(0, None)
+    }
+
+    pub fn new_upon<ParentTableType>(parent: ParentTableType) -> Self
+        where ParentTableType: 'static + NameTable + Send + Sync
+    {
+        let initial_idx = parent.initial_local() + parent.len_local();
+        ConcurrentNameTable::with_parent(initial_idx, Some(Box::new(parent)))
+    }
+
+    fn with_parent(initial_idx: usize,
+                   parent: Option<Box<dyn NameTable + Send + Sync>>)
+                   -> Self {
+        ConcurrentNameTable {
+            initial_idx,
+            reserved: AtomicUsize::new(0),
+            len: AtomicUsize::new(0),
+            chunks: (0..CHUNK_COUNT).map(|_| OnceLock::new()).collect(),
+            shards: (0..SHARD_COUNT).map(|_| RwLock::new(HashMap::new())).collect(),
+            parent,
+        }
+    }
+
+    /// Returns the index of `name`, adding it to this table if neither the
+    /// table nor its parents contain it yet.
+    pub fn intern(&self, name: &str) -> usize {
+        if let Some(idx) = self.find(name) {
+            return idx;
+        }
+        let mut shard = self.shard(name).write().unwrap_or_else(|err| err.into_inner());
+        if let Some(&idx) = shard.get(name) {
+            return self.initial_idx + idx;
+        }
+        let name: Arc<str> = Arc::from(name);
+        let idx = self.reserved.fetch_add(1, Ordering::Relaxed);
+        let stored = self.slot(idx).set(name.clone());
+        debug_assert!(stored.is_ok());
+        shard.insert(name, idx);
+        // Names become visible in index order, once every earlier one is
+        // stored too. Only writers wait here, for writers that reserved an
+        // earlier index and are about to store it; the length is published
+        // before the shard lock is released, so `find` never returns an
+        // index that `at` would not accept.
+        loop {
+            match self.len.compare_exchange_weak(idx, idx + 1, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(_) => thread::yield_now(),
+            }
+        }
+        self.initial_idx + idx
+    }
+
+    fn shard(&self, name: &str) -> &Shard {
+        &self.shards[(name_hash(name) >> 32) as usize % SHARD_COUNT]
+    }
+
+    fn slot(&self, idx: usize) -> &Slot {
+        // Chunk `k` holds `FIRST_CHUNK_LEN << k` slots, starting at local index
+        // `FIRST_CHUNK_LEN * (2^k - 1)`.
+        let position = idx / FIRST_CHUNK_LEN + 1;
+        let chunk = (usize::BITS - 1 - position.leading_zeros()) as usize;
+        let start = FIRST_CHUNK_LEN * ((1 << chunk) - 1);
+        let slots = self.chunks[chunk]
+            .get_or_init(|| (0..FIRST_CHUNK_LEN << chunk).map(|_| OnceLock::new()).collect());
+        &slots[idx - start]
+    }
+}
+
+impl NameTable for ConcurrentNameTable {
+    fn initial_local(&self) -> usize {
+        self.initial_idx
+    }
+    fn len_local(&self) -> usize {
+        self.len.load(Ordering::Acquire)
+    }
+    fn at_local(&self, idx: usize) -> &str {
+        if idx >= self.len_local() {
+            panic!("nametable: local index {} is out of bound for a layer of {} names",
+                   idx,
+                   self.len_local());
+        }
+        // `len_local` only counts names that are already stored.
+        self.slot(idx).get().expect("nametable: name below the published length is not stored")
+    }
+    fn parent(&self) -> Option<&dyn NameTable> {
+        self.parent.as_ref().map(|boxed| boxed.as_ref() as &dyn NameTable)
+    }
+
+    fn find_local(&self, name: &str) -> Option<usize> {
+        let shard = self.shard(name).read().unwrap_or_else(|err| err.into_inner());
+        shard.get(name).cloned()
+    }
+}
+
+impl<T: NameTableIdx> Index<T> for ConcurrentNameTable {
+    type Output = str;
+
+    fn index(&self, _index: T) -> &str {
+        self.at(_index.to_index())
+    }
+}
@@ -0,0 +1,91 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// An open-addressing index from name hashes to local indices.
+///
+/// Entries are only ever appended (or removed from the end), so the index
+/// stores the hash of every local entry in index order and rebuilds its probe
+/// table from those hashes when it grows. The caller supplies the equality
+/// check, so the index never holds a copy of the names themselves.
+pub struct HashIndex {
+    hashes: Vec<u64>,
+    slots: Vec<usize>,
+}
+
+const EMPTY_SLOT: usize = 0;
+const INITIAL_SLOTS: usize = 16;
+
+impl HashIndex {
+    pub fn new() -> Self {
+        HashIndex {
+            hashes: Vec::new(),
+            slots: Vec::new(),
+        }
+    }
+
+    pub fn find<F: Fn(usize) -> bool>(&self, hash: u64, is_match: F) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut pos = (hash as usize) & mask;
+        loop {
+            let slot = self.slots[pos];
+            if slot == EMPTY_SLOT {
+                return None;
+            }
+            let idx = slot - 1;
+            if self.hashes[idx] == hash && is_match(idx) {
+                return Some(idx);
+            }
+            pos = (pos + 1) & mask;
+        }
+    }
+
+    /// Registers the next local index, `self.len()`, under `hash`.
+    pub fn push(&mut self, hash: u64) {
+        if (self.hashes.len() + 1) * 2 > self.slots.len() {
+            self.grow();
+        }
+        let idx = self.hashes.len();
+        self.hashes.push(hash);
+        self.insert_slot(idx);
+    }
+
+    /// Forgets every local index from `len` on. Indices are removed newest
+    /// first, so each one ends the probe run it was inserted into and its slot
+    /// can simply be emptied.
+    pub fn truncate(&mut self, len: usize) {
+        let mask = self.slots.len().wrapping_sub(1);
+        while self.hashes.len() > len {
+            let idx = self.hashes.len() - 1;
+            let mut pos = (self.hashes[idx] as usize) & mask;
+            while self.slots[pos] != idx + 1 {
+                pos = (pos + 1) & mask;
+            }
+            self.slots[pos] = EMPTY_SLOT;
+            self.hashes.pop();
+        }
+    }
+
+    fn grow(&mut self) {
+        let new_len = if self.slots.is_empty() {
+            INITIAL_SLOTS
+        } else {
+            self.slots.len() * 2
+        };
+        self.slots = vec![EMPTY_SLOT; new_len];
+        for idx in 0..self.hashes.len() {
+            self.insert_slot(idx);
+        }
+    }
+
+    fn insert_slot(&mut self, idx: usize) {
+        let mask = self.slots.len() - 1;
+        let mut pos = (self.hashes[idx] as usize) & mask;
+        while self.slots[pos] != EMPTY_SLOT {
+            pos = (pos + 1) & mask;
+        }
+        self.slots[pos] = idx + 1;
+    }
+}
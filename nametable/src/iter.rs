@@ -0,0 +1,161 @@
+use core::ops::Range;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use nametable::NameTable;
+
+/// Iteration over the contents of any `NameTable`, trait objects included.
+///
+/// These live outside `NameTable` because their iterators borrow the table by
+/// its concrete type, which a method on a trait object cannot name. Every
+/// table gets them through the blanket impl.
+pub trait NameTableExt: NameTable {
+    /// Iterates over `(index, name)` for every entry of this table and its
+    /// parents, in index order.
+    #[cfg(feature = "alloc")]
+    fn iter<'a>(&'a self) -> Iter<'a, Self> {
+        Iter::new(self)
+    }
+
+    /// Iterates over `(index, name)` for the entries of this layer only.
+    fn iter_local<'a>(&'a self) -> IterLocal<'a, Self> {
+        IterLocal::new(self)
+    }
+
+    /// Iterates over the index range of this layer and then of each parent,
+    /// down to the root of the chain.
+    fn layers<'a>(&'a self) -> Layers<'a> {
+        Layers::new(self)
+    }
+}
+
+impl<T: NameTable + ?Sized> NameTableExt for T {}
+
+/// Iterator over the entries of a single table layer, yielding each entry's
+/// global index together with its name. Returned by `NameTableExt::iter_local`.
+pub struct IterLocal<'a, T: 'a + NameTable + ?Sized = dyn NameTable + 'a> {
+    table: &'a T,
+    range: Range<usize>,
+}
+
+impl<'a, T: 'a + NameTable + ?Sized> IterLocal<'a, T> {
+    pub(crate) fn new(table: &'a T) -> Self {
+        IterLocal {
+            table,
+            range: 0..table.len_local(),
+        }
+    }
+}
+
+impl<'a, T: 'a + NameTable + ?Sized> Iterator for IterLocal<'a, T> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let table = self.table;
+        self.range
+            .next()
+            .map(|idx| (table.initial_local() + idx, table.at_local(idx)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a, T: 'a + NameTable + ?Sized> DoubleEndedIterator for IterLocal<'a, T> {
+    fn next_back(&mut self) -> Option<(usize, &'a str)> {
+        let table = self.table;
+        self.range
+            .next_back()
+            .map(|idx| (table.initial_local() + idx, table.at_local(idx)))
+    }
+}
+
+impl<'a, T: 'a + NameTable + ?Sized> ExactSizeIterator for IterLocal<'a, T> {}
+
+/// Iterator over every entry of a table and its parents in index order, that
+/// is, starting from the root of the parent chain. Returned by
+/// `NameTableExt::iter`.
+#[cfg(feature = "alloc")]
+pub struct Iter<'a, T: 'a + NameTable + ?Sized = dyn NameTable + 'a> {
+    pending: Vec<&'a dyn NameTable>,
+    current: Option<IterLocal<'a>>,
+    top: IterLocal<'a, T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: 'a + NameTable + ?Sized> Iter<'a, T> {
+    pub(crate) fn new(table: &'a T) -> Self {
+        let mut pending = Vec::new();
+        let mut layer = table.parent();
+        while let Some(parent_table) = layer {
+            pending.push(parent_table);
+            layer = parent_table.parent();
+        }
+        let current = pending.pop().map(IterLocal::new);
+        Iter {
+            pending,
+            current,
+            top: IterLocal::new(table),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: 'a + NameTable + ?Sized> Iterator for Iter<'a, T> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        while let Some(ref mut layer_iter) = self.current {
+            if let Some(entry) = layer_iter.next() {
+                return Some(entry);
+            }
+            self.current = self.pending.pop().map(IterLocal::new);
+        }
+        self.top.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.current.as_ref().map_or(0, |layer_iter| layer_iter.len())
+            + self.pending.iter().map(|table| table.len_local()).sum::<usize>()
+            + self.top.len();
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: 'a + NameTable + ?Sized> ExactSizeIterator for Iter<'a, T> {}
+
+/// Iterator over the index ranges of a table and each of its parents, from the
+/// table itself down to the root of the parent chain. Returned by
+/// `NameTableExt::layers`.
+pub struct Layers<'a> {
+    first: Option<Range<usize>>,
+    next: Option<&'a dyn NameTable>,
+}
+
+impl<'a> Layers<'a> {
+    pub(crate) fn new<T: NameTable + ?Sized>(table: &'a T) -> Self {
+        Layers {
+            first: Some(local_range(table)),
+            next: table.parent(),
+        }
+    }
+}
+
+impl<'a> Iterator for Layers<'a> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        if let Some(range) = self.first.take() {
+            return Some(range);
+        }
+        let table = self.next?;
+        self.next = table.parent();
+        Some(local_range(table))
+    }
+}
+
+fn local_range<T: NameTable + ?Sized>(table: &T) -> Range<usize> {
+    let initial = table.initial_local();
+    initial..initial + table.len_local()
+}
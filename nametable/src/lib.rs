@@ -1,3 +1,63 @@
+#![no_std]
+
+//! Static tables work on `core` alone. `DynamicNameTable`, `FrozenNameTable`,
+//! `MappedNameTable`, `NameMap`, snapshots and `NameTableExt::iter` need the
+//! `alloc` feature, and `ConcurrentNameTable` needs `std`, which is on by
+//! default and implies `alloc`.
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature = "serde")]
+extern crate serde;
+
+// The original module spells out returns and lifetimes throughout.
+#[allow(clippy::needless_return, clippy::needless_lifetimes)]
 mod nametable;
+#[cfg(feature = "alloc")]
+mod hash_index;
+mod perfect_hash;
+mod iter;
+#[cfg(feature = "alloc")]
+mod snapshot;
+mod lookup;
+#[cfg(feature = "alloc")]
+mod binary;
+#[cfg(feature = "std")]
+mod concurrent;
+#[cfg(feature = "alloc")]
+mod name_map;
 
 pub use nametable::*;
+pub use perfect_hash::PerfectHash;
+#[cfg(feature = "alloc")]
+pub use perfect_hash::PerfectHashBuf;
+pub use iter::{IterLocal, Layers, NameTableExt};
+#[cfg(feature = "alloc")]
+pub use iter::Iter;
+#[cfg(feature = "alloc")]
+pub use snapshot::{DynamicNameTableSnapshot, SnapshotError};
+#[cfg(feature = "alloc")]
+pub use binary::{MappedNameTable, FormatError, BINARY_FORMAT_VERSION, BINARY_MAGIC};
+#[cfg(feature = "std")]
+pub use concurrent::ConcurrentNameTable;
+#[cfg(feature = "alloc")]
+pub use name_map::{NameMap, NameMapIter};
+
+/// Expands to the given items only with the `alloc` feature. Generated tables
+/// wrap their `DynamicNameTable` constructors in it, since the features of
+/// this crate cannot be seen from the crate the table is generated into.
+#[doc(hidden)]
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! alloc_items {
+    ($($item:item)*) => { $($item)* };
+}
+
+#[doc(hidden)]
+#[cfg(not(feature = "alloc"))]
+#[macro_export]
+macro_rules! alloc_items {
+    ($($item:item)*) => {};
+}
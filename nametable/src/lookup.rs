@@ -0,0 +1,72 @@
+use nametable::{name_hash, Offset};
+use perfect_hash::PerfectHash;
+
+/// A sorted hash index stored as two parallel columns: entry `i` maps
+/// `hashes[i]` to the local index `idxes[i]`, and entries are sorted by hash.
+/// Keeping the columns apart means narrow indices are not padded to the
+/// alignment of the hashes.
+#[derive(Clone, Copy)]
+pub(crate) struct SplitHashEntries<'a, O: Offset = usize> {
+    pub hashes: &'a [u64],
+    pub idxes: &'a [O],
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, O: Offset> SplitHashEntries<'a, O> {
+    /// No sorted index, for layers looked up through a perfect hash alone.
+    pub const EMPTY: SplitHashEntries<'a, O> = SplitHashEntries {
+        hashes: &[],
+        idxes: &[],
+    };
+}
+
+/// A borrowed view of one hashed table layer: a names blob, the offsets
+/// delimiting each name in it, and the indices used to find names. Shared by
+/// `StaticHashedNameTable`, `FrozenNameTable` and `MappedNameTable`.
+pub(crate) struct HashedNames<'a, O: Offset = usize> {
+    pub names: &'a str,
+    pub offsets: &'a [O],
+    pub hashes: SplitHashEntries<'a, O>,
+    pub perfect_hash: Option<PerfectHash<'a, O>>,
+}
+
+impl<'a, O: Offset> HashedNames<'a, O> {
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn at(&self, idx: usize) -> &'a str {
+        &self.names[self.offsets[idx].to_usize()..self.offsets[idx + 1].to_usize()]
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        if let Some(ref perfect_hash) = self.perfect_hash {
+            perfect_hash.find(name).filter(|&idx| self.at(idx) == name)
+        } else if !self.hashes.hashes.is_empty() {
+            self.find_hashing(name)
+        } else {
+            (0..self.len()).find(|&idx| self.at(idx) == name)
+        }
+    }
+
+    fn find_hashing(&self, name: &str) -> Option<usize> {
+        // Entries are sorted by hash, and distinct names may share a hash, so
+        // every entry in the run matching the target has to be checked.
+        let target = name_hash(name);
+        let hashes = self.hashes.hashes;
+        let count = hashes.len();
+        let (mut low, mut high) = (0, count);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if hashes[mid] < target {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        (low..count)
+            .take_while(|&entry| hashes[entry] == target)
+            .map(|entry| self.hashes.idxes[entry].to_usize())
+            .find(|&idx| self.at(idx) == name)
+    }
+}
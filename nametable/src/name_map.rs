@@ -0,0 +1,152 @@
+use core::iter::{Enumerate, FilterMap, FromIterator};
+use core::ops::{Index, IndexMut};
+use core::slice;
+use alloc::vec::Vec;
+use nametable::{NameTable, NameTableIdx};
+
+/// A map from table indices to values, stored densely by index.
+///
+/// Indices are global, so one map can hold values for the names of a table
+/// and of all its parents, and an index from any layer of the chain (or a
+/// generated `Names` variant) can be used as a key. Lookups are a bounds check
+/// and an array read; the map only grows as far as the highest index inserted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameMap<V> {
+    values: Vec<Option<V>>,
+    len: usize,
+}
+
+impl<V> NameMap<V> {
+    pub fn new() -> Self {
+        NameMap {
+            values: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Creates an empty map with room for every index of `table` and its
+    /// parents.
+    pub fn for_table<T: NameTable + ?Sized>(table: &T) -> Self {
+        NameMap {
+            values: Vec::with_capacity(table.initial_local() + table.len_local()),
+            len: 0,
+        }
+    }
+
+    /// The number of indices that have a value.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get<T: NameTableIdx>(&self, idx: T) -> Option<&V> {
+        self.values.get(idx.to_index()).and_then(|value| value.as_ref())
+    }
+
+    pub fn get_mut<T: NameTableIdx>(&mut self, idx: T) -> Option<&mut V> {
+        self.values.get_mut(idx.to_index()).and_then(|value| value.as_mut())
+    }
+
+    pub fn contains<T: NameTableIdx>(&self, idx: T) -> bool {
+        self.get(idx).is_some()
+    }
+
+    /// Sets the value of `idx`, returning its previous value.
+    pub fn insert<T: NameTableIdx>(&mut self, idx: T, value: V) -> Option<V> {
+        let idx = idx.to_index();
+        if idx >= self.values.len() {
+            self.values.resize_with(idx + 1, || None);
+        }
+        let previous = self.values[idx].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn remove<T: NameTableIdx>(&mut self, idx: T) -> Option<V> {
+        let previous = self.values.get_mut(idx.to_index()).and_then(|value| value.take());
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous
+    }
+
+    /// Looks up `name` in `table`, and then its index in this map.
+    pub fn get_by_name<T: NameTable + ?Sized>(&self, table: &T, name: &str) -> Option<&V> {
+        table.find(name).and_then(|idx| self.get(idx))
+    }
+
+    /// Iterates over `(index, value)` for every index with a value, in index
+    /// order.
+    pub fn iter<'a>(&'a self) -> NameMapIter<'a, V> {
+        let present: PresentFn<'a, V> = present;
+        NameMapIter { inner: self.values.iter().enumerate().filter_map(present) }
+    }
+}
+
+impl<V> Default for NameMap<V> {
+    fn default() -> Self {
+        NameMap::new()
+    }
+}
+
+impl<V, T: NameTableIdx> Index<T> for NameMap<V> {
+    type Output = V;
+
+    fn index(&self, _index: T) -> &V {
+        let idx = _index.to_index();
+        match self.get(idx) {
+            Some(value) => value,
+            None => panic!("nametable: index {} has no value in this map", idx),
+        }
+    }
+}
+
+impl<V, T: NameTableIdx> IndexMut<T> for NameMap<V> {
+    fn index_mut(&mut self, _index: T) -> &mut V {
+        let idx = _index.to_index();
+        match self.get_mut(idx) {
+            Some(value) => value,
+            None => panic!("nametable: index {} has no value in this map", idx),
+        }
+    }
+}
+
+impl<V, T: NameTableIdx> Extend<(T, V)> for NameMap<V> {
+    fn extend<I: IntoIterator<Item = (T, V)>>(&mut self, iter: I) {
+        for (idx, value) in iter {
+            self.insert(idx, value);
+        }
+    }
+}
+
+impl<V, T: NameTableIdx> FromIterator<(T, V)> for NameMap<V> {
+    fn from_iter<I: IntoIterator<Item = (T, V)>>(iter: I) -> Self {
+        let mut map = NameMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+type PresentFn<'a, V> = fn((usize, &'a Option<V>)) -> Option<(usize, &'a V)>;
+
+fn present<V>((idx, value): (usize, &Option<V>)) -> Option<(usize, &V)> {
+    value.as_ref().map(|value| (idx, value))
+}
+
+/// Iterator over the entries of a `NameMap`, returned by `NameMap::iter`.
+pub struct NameMapIter<'a, V: 'a> {
+    inner: FilterMap<Enumerate<slice::Iter<'a, Option<V>>>, PresentFn<'a, V>>,
+}
+
+impl<'a, V: 'a> Iterator for NameMapIter<'a, V> {
+    type Item = (usize, &'a V);
+
+    fn next(&mut self) -> Option<(usize, &'a V)> {
+        self.inner.next()
+    }
+}
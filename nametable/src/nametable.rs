@@ -1,10 +1,54 @@
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
-
+use core::error;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use hash_index::HashIndex;
+use perfect_hash::PerfectHash;
+#[cfg(feature = "alloc")]
+use perfect_hash::PerfectHashBuf;
+use lookup::{HashedNames, SplitHashEntries};
+
+/// Identifies the algorithm behind `name_hash`.
+pub const NAME_HASH_ALGORITHM: &str = "fnv1a-64";
+
+/// Version of `name_hash`'s output. Generated tables record the version their
+/// `PERFECT_HASH` was computed with, and `StaticHashedNameTable::with_hash_version`
+/// rejects data from any other version.
+pub const NAME_HASH_VERSION: u32 = 1;
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Hashes a name with 64-bit FNV-1a over its UTF-8 bytes.
+///
+/// The result is baked into generated tables at build time and recomputed
+/// at run time, so it must never depend on the toolchain or the platform. Any
+/// change to its output, or to the seeded variant `PerfectHash` builds on,
+/// must bump `NAME_HASH_VERSION`.
 pub fn name_hash(str_: &str) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    str_.hash(&mut hasher);
-    hasher.finish()
+    fnv1a(FNV_OFFSET_BASIS, str_)
+}
+
+pub(crate) fn fnv1a(basis: u64, str_: &str) -> u64 {
+    fnv1a_bytes(basis, str_.as_bytes())
+}
+
+pub(crate) fn fnv1a_bytes(basis: u64, bytes: &[u8]) -> u64 {
+    let mut hash = basis;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
 }
 
 pub trait NameTableIdx {
@@ -17,12 +61,79 @@ impl NameTableIdx for usize {
     }
 }
 
+/// The unsigned integer type a static table stores its name offsets and local
+/// indices as. Narrower types shrink the generated `INDEX_DATA` and perfect
+/// hash map, as long as the names blob and the entry count fit.
+pub trait Offset: Copy + Send + Sync + 'static {
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_offset {
+    ($($ty:ty),*) => {
+        $(
+            impl Offset for $ty {
+                #[allow(clippy::unnecessary_cast)]
+                fn to_usize(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    }
+}
+
+impl_offset!(u8, u16, u32, usize);
+
+/// The error of a generated `Names::from_str` for a string the table does
+/// not contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownName;
+
+impl fmt::Display for UnknownName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown name")
+    }
+}
+
+impl error::Error for UnknownName {}
+
+/// The error of a generated `Names::try_from` for an index outside the
+/// table's `INITIAL..INITIAL + COUNT`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexOutOfRange {
+    pub index: usize,
+    pub initial: usize,
+    pub count: usize,
+}
+
+impl fmt::Display for IndexOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "index {} is outside the table's range {}..{}",
+               self.index,
+               self.initial,
+               self.initial + self.count)
+    }
+}
+
+impl error::Error for IndexOutOfRange {}
+
+#[cold]
+fn out_of_bound(idx: usize, start: usize, end: usize, depth: usize) -> ! {
+    panic!("nametable: index {} is out of bound for the range {}..{} of a table {} layer(s) deep",
+           idx,
+           start,
+           end,
+           depth);
+}
+
+#[allow(clippy::len_without_is_empty)]
 pub trait NameTable {
-    fn parent<'a>(&'a self) -> Option<&'a NameTable>;
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable>;
 
     fn initial_local(&self) -> usize;
     fn len_local(&self) -> usize;
     fn at_local<'a>(&'a self, idx: usize) -> &'a str;
+    #[allow(clippy::manual_find)]
     fn find_local(&self, name: &str) -> Option<usize> {
         for i in 0..self.len_local() {
             if self.at_local(i) == name {
@@ -36,15 +147,40 @@ pub trait NameTable {
         return self.len_local() + self.parent().map_or(0usize, |parent_table| parent_table.len());
     }
 
-    fn at<'a>(&'a self, idx: usize) -> &'a str {
+    /// Like `at_local`, but returns `None` instead of panicking when `idx` is
+    /// not below `len_local()`.
+    fn get_local<'a>(&'a self, idx: usize) -> Option<&'a str> {
+        if idx < self.len_local() {
+            return Some(self.at_local(idx));
+        }
+        return None;
+    }
+
+    /// Like `at`, but returns `None` instead of panicking when `idx` is not
+    /// covered by this table or any of its parents.
+    fn get<'a>(&'a self, idx: usize) -> Option<&'a str> {
         let initial = self.initial_local();
         if idx >= initial {
-            return self.at_local(idx - initial);
-        } else if let Some(parent_table) = self.parent().as_ref() {
-            &parent_table.at(idx)
-        } else {
-            panic!("access out of bound");
+            return self.get_local(idx - initial);
+        } else if let Some(parent_table) = self.parent() {
+            return parent_table.get(idx);
         }
+        return None;
+    }
+
+    fn at<'a>(&'a self, idx: usize) -> &'a str {
+        if let Some(name) = self.get(idx) {
+            return name;
+        }
+        let mut depth = 1;
+        let mut start = self.initial_local();
+        let mut layer = self.parent();
+        while let Some(parent_table) = layer {
+            depth += 1;
+            start = parent_table.initial_local();
+            layer = parent_table.parent();
+        }
+        out_of_bound(idx, start, self.initial_local() + self.len_local(), depth);
     }
 
     fn find(&self, name: &str) -> Option<usize> {
@@ -58,14 +194,57 @@ pub trait NameTable {
     }
 }
 
-pub struct StaticNameTable {
+/// The type a table keeps its parent as, the last type parameter of the
+/// table types.
+///
+/// It defaults to `dyn NameTable`, for tables made with `new` or layered with
+/// `new_upon`, which accepts any parent. Tables made with `new_shared` or
+/// layered with `new_upon_shared` keep their parent as
+/// `dyn NameTable + Send + Sync` instead, so that they are `Send` and `Sync`
+/// themselves and can be put in a `static` or behind an `Arc`.
+pub trait ParentTable: NameTable + 'static {
+    fn as_table(&self) -> &dyn NameTable;
+}
+
+impl ParentTable for dyn NameTable {
+    fn as_table(&self) -> &dyn NameTable {
+        self
+    }
+}
+
+impl ParentTable for dyn NameTable + Send + Sync {
+    fn as_table(&self) -> &dyn NameTable {
+        self
+    }
+}
+
+/// The parent of a static table: owned by the table when layered with
+/// `new_upon` or `new_upon_shared`, or borrowed for the whole program when
+/// layered with `new_upon_static`, which needs no allocation.
+enum StaticParent<P: ?Sized + 'static> {
+    #[cfg(feature = "alloc")]
+    Owned(Box<P>),
+    Borrowed(&'static P),
+}
+
+impl<P: ?Sized + ParentTable> StaticParent<P> {
+    fn as_table(&self) -> &dyn NameTable {
+        match *self {
+            #[cfg(feature = "alloc")]
+            StaticParent::Owned(ref boxed) => boxed.as_table(),
+            StaticParent::Borrowed(table) => table.as_table(),
+        }
+    }
+}
+
+pub struct StaticNameTable<O: Offset = usize, P: ?Sized + ParentTable = dyn NameTable> {
     initial_idx: usize,
     names: &'static str,
-    name_offsets: &'static [usize],
-    parent: Option<Box<NameTable>>,
+    name_offsets: &'static [O],
+    parent: Option<StaticParent<P>>,
 }
 
-impl NameTable for StaticNameTable {
+impl<O: Offset, P: ?Sized + ParentTable> NameTable for StaticNameTable<O, P> {
     fn initial_local(&self) -> usize {
         self.initial_idx
     }
@@ -73,15 +252,15 @@ impl NameTable for StaticNameTable {
         self.name_offsets.len() - 1
     }
     fn at_local<'a>(&'a self, idx: usize) -> &'a str {
-        &self.names[self.name_offsets[idx]..self.name_offsets[idx + 1]]
+        &self.names[self.name_offsets[idx].to_usize()..self.name_offsets[idx + 1].to_usize()]
     }
-    fn parent<'a>(&'a self) -> Option<&'a NameTable> {
-        self.parent.as_ref().map(|boxed| boxed.as_ref())
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable> {
+        self.parent.as_ref().map(|parent| parent.as_table())
     }
 }
 
-impl StaticNameTable {
-    pub fn new(names_: &'static str, name_offsets_: &'static [usize]) -> Self {
+impl<O: Offset> StaticNameTable<O> {
+    pub const fn new(names_: &'static str, name_offsets_: &'static [O]) -> Self {
         return StaticNameTable {
             initial_idx: 0usize,
             names: names_,
@@ -90,169 +269,561 @@ impl StaticNameTable {
         };
     }
 
+    #[cfg(feature = "alloc")]
     pub fn new_upon<ParentTableType: 'static + NameTable>(names_: &'static str,
-                                                     name_offsets_: &'static [usize],
-                                                     parent: ParentTableType)
-                                                     -> Self {
+                                                          name_offsets_: &'static [O],
+                                                          parent: ParentTableType)
+                                                          -> Self {
+        StaticNameTable {
+            initial_idx: parent.initial_local() + parent.len_local(),
+            names: names_,
+            name_offsets: name_offsets_,
+            parent: Some(StaticParent::Owned(Box::new(parent))),
+        }
+    }
+}
+
+impl<O: Offset> StaticNameTable<O, dyn NameTable + Send + Sync> {
+    /// Like `new`, but typed to take `Send` and `Sync` parents, so that the
+    /// table is `Send` and `Sync` too and can be put in a `static`.
+    pub const fn new_shared(names_: &'static str, name_offsets_: &'static [O]) -> Self {
+        StaticNameTable {
+            initial_idx: 0usize,
+            names: names_,
+            name_offsets: name_offsets_,
+            parent: None,
+        }
+    }
+
+    /// Like `new_upon`, but for a parent that is `Send` and `Sync`, such as
+    /// an `Arc` shared with other tables, so that the new table is too.
+    #[cfg(feature = "alloc")]
+    pub fn new_upon_shared<ParentTableType>(names_: &'static str,
+                                            name_offsets_: &'static [O],
+                                            parent: ParentTableType)
+                                            -> Self
+        where ParentTableType: 'static + NameTable + Send + Sync
+    {
         return StaticNameTable {
             initial_idx: parent.initial_local() + parent.len_local(),
             names: names_,
             name_offsets: name_offsets_,
-            parent: Some(Box::new(parent)),
+            parent: Some(StaticParent::Owned(Box::new(parent))),
         };
     }
 
+    /// Layers a table upon a parent that lives for the whole program, without
+    /// allocating, so it can initialize a `static`. For generated tables only:
+    /// `initial_idx_` is not checked against where `parent` ends, and they
+    /// pass their `INITIAL`, which is computed from their base's. Elsewhere,
+    /// pass `new_upon_shared` a `&'static` reference to the parent.
+    #[doc(hidden)]
+    pub const fn new_upon_static(names_: &'static str,
+                                 name_offsets_: &'static [O],
+                                 initial_idx_: usize,
+                                 parent: &'static (dyn NameTable + Send + Sync))
+                                 -> Self {
+        return StaticNameTable {
+            initial_idx: initial_idx_,
+            names: names_,
+            name_offsets: name_offsets_,
+            parent: Some(StaticParent::Borrowed(parent)),
+        };
+    }
 }
 
-pub struct DynamicNameTable {
+/// A table that names can be interned into at run time.
+///
+/// Local names are stored back to back in one growable buffer, delimited by
+/// an offsets vector, the same layout `StaticNameTable` uses.
+#[cfg(feature = "alloc")]
+pub struct DynamicNameTable<P: ?Sized + ParentTable = dyn NameTable> {
     initial_idx: usize,
-    names: Box<Vec<String>>,
-    parent: Option<Box<NameTable>>,
+    names: String,
+    name_offsets: Vec<usize>,
+    name_index: HashIndex,
+    parent: Option<Box<P>>,
 }
 
-impl NameTable for DynamicNameTable {
+#[cfg(feature = "alloc")]
+impl<P: ?Sized + ParentTable> NameTable for DynamicNameTable<P> {
     fn initial_local(&self) -> usize {
         self.initial_idx
     }
     fn len_local(&self) -> usize {
-        self.names.len()
+        self.name_offsets.len() - 1
     }
     fn at_local<'a>(&'a self, idx: usize) -> &'a str {
-        self.names[idx].as_str()
+        &self.names[self.name_offsets[idx]..self.name_offsets[idx + 1]]
+    }
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable> {
+        self.parent.as_ref().map(|boxed| boxed.as_table())
     }
-    fn parent<'a>(&'a self) -> Option<&'a NameTable> {
-        self.parent.as_ref().map(|boxed| boxed.as_ref())
+
+    fn find_local(&self, name: &str) -> Option<usize> {
+        self.name_index.find(name_hash(name), |idx| self.at_local(idx) == name)
     }
 }
 
+#[cfg(feature = "alloc")]
+#[allow(clippy::new_without_default)]
 impl DynamicNameTable {
     pub fn new() -> Self {
         return DynamicNameTable {
             initial_idx: 0usize,
-            names: Box::new(Vec::new()),
+            names: String::new(),
+            name_offsets: vec![0],
+            name_index: HashIndex::new(),
             parent: None,
         };
     }
 
     pub fn new_upon<ParentTableType: 'static + NameTable>(parent: ParentTableType) -> Self {
+        DynamicNameTable {
+            initial_idx: parent.initial_local() + parent.len_local(),
+            names: String::new(),
+            name_offsets: vec![0],
+            name_index: HashIndex::new(),
+            parent: Some(Box::new(parent)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl DynamicNameTable<dyn NameTable + Send + Sync> {
+    /// Like `new_upon`, but for a parent that is `Send` and `Sync`, such as
+    /// an `Arc` shared with other tables, so that the new table is too.
+    pub fn new_upon_shared<ParentTableType>(parent: ParentTableType) -> Self
+        where ParentTableType: 'static + NameTable + Send + Sync
+    {
         return DynamicNameTable {
             initial_idx: parent.initial_local() + parent.len_local(),
-            names: Box::new(Vec::new()),
+            names: String::new(),
+            name_offsets: vec![0],
+            name_index: HashIndex::new(),
             parent: Some(Box::new(parent)),
         };
     }
+}
 
+#[cfg(feature = "alloc")]
+impl<P: ?Sized + ParentTable> DynamicNameTable<P> {
     pub fn intern(&mut self, name: &str) -> usize {
         self.find(name)
             .or_else(|| {
                 Some({
-                    self.names.as_mut().push(name.to_owned());
-                    self.initial_idx + self.names.len() - 1
+                    self.name_index.push(name_hash(name));
+                    self.names.push_str(name);
+                    self.name_offsets.push(self.names.len());
+                    self.initial_idx + self.len_local() - 1
                 })
             })
             .unwrap()
     }
 
+    /// Borrows this layer's storage: the local names concatenated, and the
+    /// offsets delimiting them, so that local name `i` is
+    /// `names[offsets[i]..offsets[i + 1]]`. This is the layout
+    /// `StaticNameTable::new` takes.
+    pub fn raw_parts<'a>(&'a self) -> (&'a str, &'a [usize]) {
+        (&self.names, &self.name_offsets)
+    }
+
+    /// Records the current end of this table, for `rollback`.
+    pub fn checkpoint(&self) -> Mark {
+        Mark { end: self.initial_idx + self.len_local() }
+    }
+
+    /// Forgets every name interned since `mark` was taken, truncating the
+    /// local names and the hash index back to it. Every index handed out
+    /// after the checkpoint becomes invalid: it may be out of range, or be
+    /// reused for a different name by a later `intern`.
+    ///
+    /// Fails, leaving the table unchanged, if `mark` lies before this layer's
+    /// first index or past its current end, as it does for a mark taken from
+    /// another table or one already rolled back past.
+    pub fn rollback(&mut self, mark: Mark) -> Result<(), RollbackError> {
+        let end = self.initial_idx + self.len_local();
+        if mark.end < self.initial_idx || mark.end > end {
+            return Err(RollbackError {
+                mark: mark.end,
+                initial: self.initial_idx,
+                end,
+            });
+        }
+        let len = mark.end - self.initial_idx;
+        self.name_offsets.truncate(len + 1);
+        self.names.truncate(self.name_offsets[len]);
+        self.name_index.truncate(len);
+        return Ok(());
+    }
+
+    /// Turns this table into an immutable `FrozenNameTable` with the same
+    /// names, indices and parent chain, laid out like a generated table.
+    pub fn freeze(self) -> FrozenNameTable<P> {
+        let name_refs: Vec<&str> = (0..self.len_local()).map(|idx| self.at_local(idx)).collect();
+        let perfect_hash = PerfectHashBuf::build(&name_refs);
+
+        return FrozenNameTable {
+            initial_idx: self.initial_idx,
+            names: self.names,
+            name_offsets: self.name_offsets,
+            perfect_hash,
+            parent: self.parent,
+        };
+    }
 }
 
 
-pub struct StaticHashedNameTable {
+/// A position in a `DynamicNameTable`, taken by `checkpoint`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mark {
+    end: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl Mark {
+    /// The index the next name interned after the checkpoint got.
+    pub fn index(&self) -> usize {
+        self.end
+    }
+}
+
+/// The error of `DynamicNameTable::rollback` for a mark outside the layer's
+/// `initial..end`.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RollbackError {
+    pub mark: usize,
+    pub initial: usize,
+    pub end: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "cannot roll back to index {}, outside the layer's range {}..{}",
+               self.mark,
+               self.initial,
+               self.end)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl error::Error for RollbackError {}
+
+pub struct StaticHashedNameTable<O: Offset = usize, P: ?Sized + ParentTable = dyn NameTable> {
     initial_idx: usize,
     names: &'static str,
-    name_offsets: &'static [usize],
-    hash_idxes: &'static [(u64, usize)],
-    parent: Option<Box<NameTable>>,
+    name_offsets: &'static [O],
+    hashes: SplitHashEntries<'static, O>,
+    perfect_hash: Option<PerfectHash<'static, O>>,
+    parent: Option<StaticParent<P>>,
 }
 
-impl StaticHashedNameTable {
-    pub fn new(names_: &'static str,
-               name_offsets_: &'static [usize],
-               hash_idxes_: &'static [(u64, usize)])
+impl<O: Offset> StaticHashedNameTable<O> {
+    /// `hashes_` holds the `name_hash` of every local name, sorted, and
+    /// `hash_idxes_` the local index each of those hashes belongs to.
+    pub const fn new(names_: &'static str,
+               name_offsets_: &'static [O],
+               hashes_: &'static [u64],
+               hash_idxes_: &'static [O])
                -> Self {
 
         return StaticHashedNameTable {
             initial_idx: 0usize,
             names: names_,
             name_offsets: name_offsets_,
-            hash_idxes: hash_idxes_,
+            hashes: split_hash_entries(hashes_, hash_idxes_),
+            perfect_hash: None,
             parent: None,
         };
     }
 
+    #[cfg(feature = "alloc")]
     pub fn new_upon<ParentTableType: 'static + NameTable>(names_: &'static str,
-                                                     name_offsets_: &'static [usize],
-                                                     hash_idxes_: &'static [(u64, usize)],
-                                                     parent: ParentTableType)
-                                                     -> Self {
+                                                          name_offsets_: &'static [O],
+                                                          hashes_: &'static [u64],
+                                                          hash_idxes_: &'static [O],
+                                                          parent: ParentTableType)
+                                                          -> Self {
+        StaticHashedNameTable {
+            initial_idx: parent.initial_local() + parent.len_local(),
+            names: names_,
+            name_offsets: name_offsets_,
+            hashes: split_hash_entries(hashes_, hash_idxes_),
+            perfect_hash: None,
+            parent: Some(StaticParent::Owned(Box::new(parent))),
+        }
+    }
+}
+
+impl<O: Offset> StaticHashedNameTable<O, dyn NameTable + Send + Sync> {
+    /// Like `StaticNameTable::new_shared`: typed to take `Send` and `Sync`
+    /// parents, so that the table can be put in a `static`.
+    pub const fn new_shared(names_: &'static str,
+                            name_offsets_: &'static [O],
+                            hashes_: &'static [u64],
+                            hash_idxes_: &'static [O])
+                            -> Self {
+        StaticHashedNameTable {
+            initial_idx: 0usize,
+            names: names_,
+            name_offsets: name_offsets_,
+            hashes: split_hash_entries(hashes_, hash_idxes_),
+            perfect_hash: None,
+            parent: None,
+        }
+    }
+
+    /// Like `StaticNameTable::new_upon_shared`: layers a table upon a parent
+    /// that is `Send` and `Sync`, so that the new table is too.
+    #[cfg(feature = "alloc")]
+    pub fn new_upon_shared<ParentTableType>(names_: &'static str,
+                                            name_offsets_: &'static [O],
+                                            hashes_: &'static [u64],
+                                            hash_idxes_: &'static [O],
+                                            parent: ParentTableType)
+                                            -> Self
+        where ParentTableType: 'static + NameTable + Send + Sync
+    {
 
         return StaticHashedNameTable {
             initial_idx: parent.initial_local() + parent.len_local(),
             names: names_,
             name_offsets: name_offsets_,
-            hash_idxes: hash_idxes_,
-            parent: Some(Box::new(parent)),
+            hashes: split_hash_entries(hashes_, hash_idxes_),
+            perfect_hash: None,
+            parent: Some(StaticParent::Owned(Box::new(parent))),
         };
     }
 
+    /// Like `StaticNameTable::new_upon_static`: layers a table upon a parent
+    /// that lives for the whole program, without allocating. For generated
+    /// tables only, as `initial_idx_` is not checked.
+    #[doc(hidden)]
+    pub const fn new_upon_static(names_: &'static str,
+                                 name_offsets_: &'static [O],
+                                 hashes_: &'static [u64],
+                                 hash_idxes_: &'static [O],
+                                 initial_idx_: usize,
+                                 parent: &'static (dyn NameTable + Send + Sync))
+                                 -> Self {
+        return StaticHashedNameTable {
+            initial_idx: initial_idx_,
+            names: names_,
+            name_offsets: name_offsets_,
+            hashes: split_hash_entries(hashes_, hash_idxes_),
+            perfect_hash: None,
+            parent: Some(StaticParent::Borrowed(parent)),
+        };
+    }
+}
+
+impl<O: Offset, P: ?Sized + ParentTable> StaticHashedNameTable<O, P> {
+    /// Checks that the hash data passed to the constructor was computed with
+    /// `NAME_HASH_VERSION`, panicking otherwise. Lookups against mismatched
+    /// hash data would silently fail, so generated tables always call this.
+    pub fn with_hash_version(self, version: u32) -> Self {
+        if version != NAME_HASH_VERSION {
+            panic!("nametable: hash data was generated with name_hash version {} but this \
+                    runtime uses version {} ({}); regenerate the table",
+                   version,
+                   NAME_HASH_VERSION,
+                   NAME_HASH_ALGORITHM);
+        }
+        self
+    }
+
+    /// Makes `find_local` go through `perfect_hash`, which must have been built
+    /// over exactly this table's local names, instead of binary searching the
+    /// hash data.
+    pub const fn with_perfect_hash(mut self, perfect_hash: PerfectHash<'static, O>) -> Self {
+        self.perfect_hash = Some(perfect_hash);
+        self
+    }
+
+    #[allow(clippy::should_implement_trait, clippy::needless_borrow)]
     pub fn index<'x, T: NameTableIdx>(&'x self, idx: T) -> &'x str {
         &self.at(idx.to_index())
     }
 
-    fn hash_enabled(&self) -> bool {
-        self.hash_idxes.len() != 0
+    /// Like `index`, but returns `None` for an index outside the table.
+    pub fn try_index<'x, T: NameTableIdx>(&'x self, idx: T) -> Option<&'x str> {
+        self.get(idx.to_index())
     }
 
-    fn find_local_hashing(&self, name: &str) -> Option<usize> {
-        let target = name_hash(name);
-        let result = self.hash_idxes.binary_search_by(|&(a, _)| a.cmp(&target));
-        match result {
-            Ok(val) => {
-                if self.at_local(self.hash_idxes[val].1) == name {
-                    Some(self.hash_idxes[val].1)
-                } else {
-                    None
-                }
-            }
-            _ => None,
+    fn local(&self) -> HashedNames<'_, O> {
+        HashedNames {
+            names: self.names,
+            offsets: self.name_offsets,
+            hashes: self.hashes,
+            perfect_hash: self.perfect_hash,
         }
     }
+}
 
-    fn find_local_fallback(&self, name: &str) -> Option<usize> {
-        for i in 0..self.len_local() {
-            if self.at_local(i) == name {
-                return Some(i);
-            }
-        }
-        return None;
+const fn split_hash_entries<O: Offset>(hashes: &'static [u64],
+                                       hash_idxes: &'static [O])
+                                       -> SplitHashEntries<'static, O> {
+    assert!(hashes.len() == hash_idxes.len(),
+            "nametable: hashes and hash_idxes must have the same length");
+    SplitHashEntries {
+        hashes,
+        idxes: hash_idxes,
     }
 }
 
-impl NameTable for StaticHashedNameTable {
+impl<O: Offset, P: ?Sized + ParentTable> NameTable for StaticHashedNameTable<O, P> {
     fn initial_local(&self) -> usize {
         self.initial_idx
     }
     fn len_local(&self) -> usize {
-        self.name_offsets.len() - 1
+        self.local().len()
     }
     fn at_local<'a>(&'a self, idx: usize) -> &'a str {
-        &self.names[self.name_offsets[idx]..self.name_offsets[idx + 1]]
+        self.local().at(idx)
     }
-    fn parent<'a>(&'a self) -> Option<&'a NameTable> {
-        self.parent.as_ref().map(|boxed| boxed.as_ref())
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable> {
+        self.parent.as_ref().map(|parent| parent.as_table())
     }
 
     fn find_local(&self, name: &str) -> Option<usize> {
-        if self.hash_enabled() {
-            self.find_local_hashing(name)
-        } else {
-            self.find_local_fallback(name)
+        self.local().find(name)
+    }
+}
+
+/// An immutable, heap-owned table produced by `DynamicNameTable::freeze`.
+///
+/// It keeps one contiguous names buffer, an offsets array and a perfect hash,
+/// like a generated `StaticHashedNameTable`, and looks names up the same way.
+#[cfg(feature = "alloc")]
+pub struct FrozenNameTable<P: ?Sized + ParentTable = dyn NameTable> {
+    initial_idx: usize,
+    names: String,
+    name_offsets: Vec<usize>,
+    perfect_hash: PerfectHashBuf,
+    parent: Option<Box<P>>,
+}
+
+#[cfg(feature = "alloc")]
+impl<P: ?Sized + ParentTable> FrozenNameTable<P> {
+    fn local<'a>(&'a self) -> HashedNames<'a> {
+        HashedNames {
+            names: &self.names,
+            offsets: &self.name_offsets,
+            hashes: SplitHashEntries::EMPTY,
+            perfect_hash: Some(self.perfect_hash.as_perfect_hash()),
         }
     }
 }
 
-use std::ops::Index;
+#[cfg(feature = "alloc")]
+impl<P: ?Sized + ParentTable> NameTable for FrozenNameTable<P> {
+    fn initial_local(&self) -> usize {
+        self.initial_idx
+    }
+    fn len_local(&self) -> usize {
+        self.local().len()
+    }
+    fn at_local<'a>(&'a self, idx: usize) -> &'a str {
+        self.local().at(idx)
+    }
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable> {
+        self.parent.as_ref().map(|boxed| boxed.as_table())
+    }
 
-impl<T: NameTableIdx> Index<T> for NameTable {
+    fn find_local(&self, name: &str) -> Option<usize> {
+        self.local().find(name)
+    }
+}
+
+/// A table shared through an `Arc` can be the parent of any number of other
+/// tables, on any thread: pass each child's `new_upon_shared` a clone of the
+/// `Arc`.
+#[cfg(feature = "alloc")]
+impl<T: NameTable + ?Sized> NameTable for Arc<T> {
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable> {
+        (**self).parent()
+    }
+    fn initial_local(&self) -> usize {
+        (**self).initial_local()
+    }
+    fn len_local(&self) -> usize {
+        (**self).len_local()
+    }
+    fn at_local<'a>(&'a self, idx: usize) -> &'a str {
+        (**self).at_local(idx)
+    }
+    fn find_local(&self, name: &str) -> Option<usize> {
+        (**self).find_local(name)
+    }
+    fn get_local<'a>(&'a self, idx: usize) -> Option<&'a str> {
+        (**self).get_local(idx)
+    }
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+    fn get<'a>(&'a self, idx: usize) -> Option<&'a str> {
+        (**self).get(idx)
+    }
+    fn at<'a>(&'a self, idx: usize) -> &'a str {
+        (**self).at(idx)
+    }
+    fn find(&self, name: &str) -> Option<usize> {
+        (**self).find(name)
+    }
+}
+
+/// A table that lives for the rest of the program, such as the one returned
+/// by a generated module's `shared()`, can be a parent by reference. Tables
+/// own their parents for `'static`, so only a `&'static` reference can be one.
+impl<'t, T: NameTable + ?Sized> NameTable for &'t T {
+    fn parent<'a>(&'a self) -> Option<&'a dyn NameTable> {
+        (**self).parent()
+    }
+    fn initial_local(&self) -> usize {
+        (**self).initial_local()
+    }
+    fn len_local(&self) -> usize {
+        (**self).len_local()
+    }
+    fn at_local<'a>(&'a self, idx: usize) -> &'a str {
+        (**self).at_local(idx)
+    }
+    fn find_local(&self, name: &str) -> Option<usize> {
+        (**self).find_local(name)
+    }
+    fn get_local<'a>(&'a self, idx: usize) -> Option<&'a str> {
+        (**self).get_local(idx)
+    }
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+    fn get<'a>(&'a self, idx: usize) -> Option<&'a str> {
+        (**self).get(idx)
+    }
+    fn at<'a>(&'a self, idx: usize) -> &'a str {
+        (**self).at(idx)
+    }
+    fn find(&self, name: &str) -> Option<usize> {
+        (**self).find(name)
+    }
+}
+
+use core::ops::Index;
+
+impl<T: NameTableIdx> Index<T> for dyn NameTable {
+    type Output = str;
+
+    fn index<'a>(&'a self, _index: T) -> &'a str {
+        self.at(_index.to_index())
+    }
+}
+
+impl<T: NameTableIdx, O: Offset, P: ?Sized + ParentTable> Index<T> for StaticNameTable<O, P> {
     type Output = str;
 
     fn index<'a>(&'a self, _index: T) -> &'a str {
@@ -260,7 +831,8 @@ impl<T: NameTableIdx> Index<T> for NameTable {
     }
 }
 
-impl<T: NameTableIdx> Index<T> for StaticNameTable {
+#[cfg(feature = "alloc")]
+impl<T: NameTableIdx, P: ?Sized + ParentTable> Index<T> for DynamicNameTable<P> {
     type Output = str;
 
     fn index<'a>(&'a self, _index: T) -> &'a str {
@@ -268,7 +840,7 @@ impl<T: NameTableIdx> Index<T> for StaticNameTable {
     }
 }
 
-impl<T: NameTableIdx> Index<T> for DynamicNameTable {
+impl<T: NameTableIdx, O: Offset, P: ?Sized + ParentTable> Index<T> for StaticHashedNameTable<O, P> {
     type Output = str;
 
     fn index<'a>(&'a self, _index: T) -> &'a str {
@@ -276,10 +848,11 @@ impl<T: NameTableIdx> Index<T> for DynamicNameTable {
     }
 }
 
-impl<T: NameTableIdx> Index<T> for StaticHashedNameTable {
+#[cfg(feature = "alloc")]
+impl<T: NameTableIdx, P: ?Sized + ParentTable> Index<T> for FrozenNameTable<P> {
     type Output = str;
 
     fn index<'a>(&'a self, _index: T) -> &'a str {
         self.at(_index.to_index())
     }
-}
\ No newline at end of file
+}
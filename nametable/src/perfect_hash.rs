@@ -0,0 +1,186 @@
+#[cfg(feature = "alloc")]
+use alloc::vec;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use nametable::{fnv1a, Offset, FNV_OFFSET_BASIS};
+
+/// Average number of keys per displacement bucket.
+#[cfg(feature = "alloc")]
+const LAMBDA: usize = 5;
+
+/// Upper bound on `d1` when searching for a bucket's displacement. `d2` only
+/// rotates the bucket's slots, so trying each of its `len` values once is
+/// enough; a bucket that fits none of the resulting pairs makes the build start
+/// over with the next seed, as in CHD, instead of searching all `len ^ 2` pairs.
+#[cfg(feature = "alloc")]
+const MAX_D1: u32 = 32;
+
+/// A minimal perfect hash over a table's local names, using the
+/// hash-and-displace (CHD) scheme.
+///
+/// `find` maps any name to a single candidate local index with one hash and
+/// two array reads; the caller still has to compare the name at that index,
+/// since names outside the table map to arbitrary candidates too.
+///
+/// The map stores local indices as `O`, matching the table it belongs to.
+#[derive(Clone, Copy)]
+pub struct PerfectHash<'a, O: Offset = usize> {
+    pub seed: u64,
+    pub displacements: &'a [[u32; 2]],
+    pub map: &'a [O],
+}
+
+impl<'a, O: Offset> PerfectHash<'a, O> {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        if self.map.is_empty() {
+            return None;
+        }
+        let hashes = PerfectHashes::new(self.seed, name);
+        let displacement = self.displacements[hashes.g as usize % self.displacements.len()];
+        let slot = hashes.displace(displacement) % self.map.len() as u32;
+        Some(self.map[slot as usize].to_usize())
+    }
+}
+
+/// An owned perfect hash, as produced by `PerfectHashBuf::build`.
+#[cfg(feature = "alloc")]
+pub struct PerfectHashBuf {
+    seed: u64,
+    displacements: Vec<[u32; 2]>,
+    map: Vec<usize>,
+}
+
+#[cfg(feature = "alloc")]
+impl PerfectHashBuf {
+    /// Builds a perfect hash over `names`, where each name's position in the
+    /// slice is its local index. If a name occurs more than once, it maps to
+    /// its first index, matching what a linear scan would find.
+    ///
+    /// Seeds are tried in a fixed order, so the result only depends on `names`.
+    pub fn build(names: &[&str]) -> Self {
+        let mut keys: Vec<(&str, usize)> = names.iter().cloned().zip(0..).collect();
+        keys.sort();
+        keys.dedup_by(|later, earlier| later.0 == earlier.0);
+
+        let mut attempt = 0u64;
+        loop {
+            attempt += 1;
+            let seed = mix(attempt);
+            if let Some(result) = PerfectHashBuf::try_build(seed, &keys) {
+                return result;
+            }
+        }
+    }
+
+    fn try_build(seed: u64, keys: &[(&str, usize)]) -> Option<Self> {
+        let len = keys.len();
+        if len == 0 {
+            return Some(PerfectHashBuf {
+                seed,
+                displacements: Vec::new(),
+                map: Vec::new(),
+            });
+        }
+
+        let hashes: Vec<PerfectHashes> =
+            keys.iter().map(|&(name, _)| PerfectHashes::new(seed, name)).collect();
+        let buckets_len = len.div_ceil(LAMBDA);
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); buckets_len];
+        for (key, hash) in hashes.iter().enumerate() {
+            buckets[hash.g as usize % buckets_len].push(key);
+        }
+        let mut bucket_order: Vec<usize> = (0..buckets_len).collect();
+        bucket_order.sort_by(|&a, &b| buckets[b].len().cmp(&buckets[a].len()));
+
+        let mut displacements = vec![[0u32, 0u32]; buckets_len];
+        let mut map: Vec<Option<usize>> = vec![None; len];
+        let mut tried_at = vec![0u64; len];
+        let mut generation = 0u64;
+        let mut placed = Vec::new();
+
+        'buckets: for &bucket in bucket_order.iter() {
+            if buckets[bucket].is_empty() {
+                break;
+            }
+            for d1 in 0..MAX_D1.min(len as u32) {
+                'displacement: for d2 in 0..len as u32 {
+                    placed.clear();
+                    generation += 1;
+                    for &key in buckets[bucket].iter() {
+                        let slot = (hashes[key].displace([d1, d2]) % len as u32) as usize;
+                        if map[slot].is_some() || tried_at[slot] == generation {
+                            continue 'displacement;
+                        }
+                        tried_at[slot] = generation;
+                        placed.push((slot, key));
+                    }
+                    displacements[bucket] = [d1, d2];
+                    for &(slot, key) in placed.iter() {
+                        map[slot] = Some(keys[key].1);
+                    }
+                    continue 'buckets;
+                }
+            }
+            return None;
+        }
+
+        Some(PerfectHashBuf {
+            seed,
+            displacements,
+            map: map.into_iter().map(|idx| idx.unwrap()).collect(),
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The `[d1, d2]` displacement of each bucket.
+    pub fn displacements(&self) -> &[[u32; 2]] {
+        &self.displacements
+    }
+
+    /// The local index stored at each slot.
+    pub fn map(&self) -> &[usize] {
+        &self.map
+    }
+
+    pub fn as_perfect_hash(&self) -> PerfectHash<'_> {
+        PerfectHash {
+            seed: self.seed,
+            displacements: &self.displacements,
+            map: &self.map,
+        }
+    }
+}
+
+struct PerfectHashes {
+    g: u32,
+    f1: u32,
+    f2: u32,
+}
+
+impl PerfectHashes {
+    fn new(seed: u64, name: &str) -> Self {
+        let h1 = mix(fnv1a(FNV_OFFSET_BASIS ^ seed, name));
+        let h2 = mix(h1 ^ 0x9e3779b97f4a7c15);
+        PerfectHashes {
+            g: (h1 >> 32) as u32,
+            f1: h1 as u32,
+            f2: h2 as u32,
+        }
+    }
+
+    fn displace(&self, displacement: [u32; 2]) -> u32 {
+        displacement[1].wrapping_add(self.f1.wrapping_mul(displacement[0])).wrapping_add(self.f2)
+    }
+}
+
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58476d1ce4e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d049bb133111eb);
+    x ^= x >> 31;
+    x
+}
@@ -0,0 +1,131 @@
+use core::error;
+use core::fmt;
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use nametable::{DynamicNameTable, NameTable, ParentTable};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+
+/// The saved state of a `DynamicNameTable`'s own layer: the index its first
+/// name gets and its names in index order. Parent layers are not part of a
+/// snapshot; they are supplied again on restore.
+///
+/// With the `serde` feature, this type implements `Serialize` and
+/// `Deserialize`, and `DynamicNameTable` serializes to the same shape.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DynamicNameTableSnapshot {
+    pub initial_idx: usize,
+    pub names: Vec<String>,
+}
+
+/// The reason a snapshot could not be restored without changing an index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// The supplied parent chain ends at `expected`, but the snapshot's
+    /// layer started at `found`.
+    InitialMismatch { expected: usize, found: usize },
+    /// The name occurs twice in the snapshot, or is already in the parent
+    /// chain, so interning it again would not reproduce its index.
+    DuplicateName(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::InitialMismatch { expected, found } => {
+                write!(f,
+                       "snapshot starts at index {}, but its parent ends at index {}",
+                       found,
+                       expected)
+            }
+            SnapshotError::DuplicateName(ref name) => {
+                write!(f, "name {:?} occurs more than once in the snapshot and its parent", name)
+            }
+        }
+    }
+}
+
+impl error::Error for SnapshotError {}
+
+impl<P: ?Sized + ParentTable> DynamicNameTable<P> {
+    /// Copies out this table's own layer.
+    pub fn snapshot(&self) -> DynamicNameTableSnapshot {
+        DynamicNameTableSnapshot {
+            initial_idx: self.initial_local(),
+            names: (0..self.len_local()).map(|idx| self.at_local(idx).to_owned()).collect(),
+        }
+    }
+}
+
+impl DynamicNameTable {
+    /// Rebuilds a table without a parent from `snapshot`.
+    pub fn restore(snapshot: DynamicNameTableSnapshot) -> Result<Self, SnapshotError> {
+        restore_into(DynamicNameTable::new(), snapshot)
+    }
+
+    /// Rebuilds a table from `snapshot` on top of `parent`, which must end
+    /// exactly where the snapshot's layer starts, so every index handed out
+    /// before the snapshot was taken still refers to the same name.
+    pub fn restore_upon<ParentTableType: 'static + NameTable>(snapshot: DynamicNameTableSnapshot,
+                                                              parent: ParentTableType)
+                                                              -> Result<Self, SnapshotError> {
+        restore_into(DynamicNameTable::new_upon(parent), snapshot)
+    }
+}
+
+impl DynamicNameTable<dyn NameTable + Send + Sync> {
+    /// Like `restore_upon`, but for a parent that is `Send` and `Sync`.
+    pub fn restore_upon_shared<ParentTableType>(snapshot: DynamicNameTableSnapshot,
+                                                parent: ParentTableType)
+                                                -> Result<Self, SnapshotError>
+        where ParentTableType: 'static + NameTable + Send + Sync
+    {
+        restore_into(DynamicNameTable::new_upon_shared(parent), snapshot)
+    }
+}
+
+fn restore_into<P: ?Sized + ParentTable>(mut table: DynamicNameTable<P>,
+                                         snapshot: DynamicNameTableSnapshot)
+                                         -> Result<DynamicNameTable<P>, SnapshotError> {
+    let expected = table.initial_local();
+    if snapshot.initial_idx != expected {
+        return Err(SnapshotError::InitialMismatch {
+            expected,
+            found: snapshot.initial_idx,
+        });
+    }
+    for name in snapshot.names {
+        let len_before = table.len_local();
+        table.intern(&name);
+        if table.len_local() == len_before {
+            return Err(SnapshotError::DuplicateName(name));
+        }
+    }
+    Ok(table)
+}
+
+#[cfg(feature = "serde")]
+impl<P: ?Sized + ParentTable> Serialize for DynamicNameTable<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DynamicNameTableSnapshot", 2)?;
+        state.serialize_field("initial_idx", &self.initial_local())?;
+        state.serialize_field("names", &LocalNames(self))?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct LocalNames<'a, P: ?Sized + ParentTable>(&'a DynamicNameTable<P>);
+
+#[cfg(feature = "serde")]
+impl<'a, P: ?Sized + ParentTable> Serialize for LocalNames<'a, P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let table = self.0;
+        serializer.collect_seq((0..table.len_local()).map(|idx| table.at_local(idx)))
+    }
+}
@@ -0,0 +1,126 @@
+use nametable::*;
+use std::slice;
+
+static NAME_DATA_1: &str = "FIRSTSECONDTHIRD";
+static INDEX_DATA_1: &[usize] = &[0, 5, 11, 16];
+
+/// Copies `bytes` into a buffer aligned to eight bytes, the way a memory map
+/// would be.
+fn aligned(bytes: &[u8]) -> Vec<u64> {
+    let mut buffer = vec![0u64; bytes.len() / 8 + 1];
+    as_bytes_mut(&mut buffer)[..bytes.len()].copy_from_slice(bytes);
+    buffer
+}
+
+fn as_bytes(buffer: &[u64]) -> &[u8] {
+    unsafe { slice::from_raw_parts(buffer.as_ptr() as *const u8, buffer.len() * 8) }
+}
+
+fn as_bytes_mut(buffer: &mut [u64]) -> &mut [u8] {
+    unsafe { slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8) }
+}
+
+fn sample_layer() -> DynamicNameTable {
+    let mut tbl = DynamicNameTable::new_upon(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    for i in 0..1000 {
+        tbl.intern(&format!("name{}", i));
+    }
+    tbl.intern("");
+    tbl.intern("caf\u{e9}");
+    tbl
+}
+
+#[test]
+fn test1() {
+    let source = sample_layer();
+    let encoded = MappedNameTable::encode_layer(&source);
+    assert!(encoded[..8] == BINARY_MAGIC);
+    let buffer = aligned(&encoded);
+
+    let tbl = MappedNameTable::from_bytes_upon(as_bytes(&buffer),
+                                               StaticNameTable::new_shared(NAME_DATA_1,
+                                                                           INDEX_DATA_1))
+        .unwrap();
+    assert!(tbl.initial_local() == 3);
+    assert!(tbl.len_local() == source.len_local());
+    assert!(tbl.len() == source.len());
+    for (idx, name) in source.iter() {
+        assert!(tbl.at(idx) == name);
+        assert!(tbl.find(name) == Some(idx));
+    }
+    assert!(&tbl[1004] == "caf\u{e9}");
+    assert!(tbl.find("SECOND") == Some(1));
+    assert!(tbl.find_local("SECOND").is_none());
+    assert!(tbl.find("UNEXIST").is_none());
+    assert!(tbl.get(1005).is_none());
+
+    let root = MappedNameTable::encode_layer(&StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let root_buffer = aligned(&root);
+    let root_tbl = MappedNameTable::from_bytes(as_bytes(&root_buffer)).unwrap();
+    assert!(root_tbl.find("THIRD") == Some(2));
+
+    let empty = MappedNameTable::encode_layer(&DynamicNameTable::new());
+    let empty_buffer = aligned(&empty);
+    let empty_tbl = MappedNameTable::from_bytes(as_bytes(&empty_buffer)).unwrap();
+    assert!(empty_tbl.len() == 0);
+    assert!(empty_tbl.find("").is_none());
+}
+
+#[test]
+fn test2() {
+    let encoded = MappedNameTable::encode_layer(&sample_layer());
+    let load = |bytes: &[u8]| {
+        let buffer = aligned(bytes);
+        let bytes = &as_bytes(&buffer)[..bytes.len()];
+        let root = StaticNameTable::new_shared(NAME_DATA_1, INDEX_DATA_1);
+        MappedNameTable::from_bytes_upon(bytes, root)
+            .err()
+    };
+    assert!(load(&encoded).is_none());
+
+    assert!(load(&encoded[..40]) == Some(FormatError::Truncated));
+    assert!(load(&encoded[..encoded.len() - 8]) == Some(FormatError::Truncated));
+
+    let mut bad_magic = encoded.clone();
+    bad_magic[0] = b'X';
+    assert!(load(&bad_magic) == Some(FormatError::BadMagic));
+
+    let mut bad_version = encoded.clone();
+    bad_version[8..12].copy_from_slice(&99u32.to_ne_bytes());
+    assert!(load(&bad_version) == Some(FormatError::UnsupportedVersion(99)));
+
+    let mut bad_hash_version = encoded.clone();
+    bad_hash_version[12..16].copy_from_slice(&(NAME_HASH_VERSION + 1).to_ne_bytes());
+    assert!(load(&bad_hash_version) ==
+            Some(FormatError::HashVersionMismatch(NAME_HASH_VERSION + 1)));
+
+    let mut foreign = encoded.clone();
+    foreign[17] = 2;
+    assert!(load(&foreign) ==
+            Some(FormatError::ForeignLayout {
+                endianness: encoded[16],
+                word_size: 2,
+            }));
+
+    let mut flipped = encoded.clone();
+    let last = flipped.len() - 1;
+    flipped[100] ^= 1;
+    flipped[last] ^= 1;
+    assert!(load(&flipped) == Some(FormatError::ChecksumMismatch));
+
+    let root = MappedNameTable::encode_layer(&sample_layer());
+    let buffer = aligned(&root);
+    assert!(MappedNameTable::from_bytes(as_bytes(&buffer)).err() ==
+            Some(FormatError::InitialMismatch {
+                expected: 0,
+                found: 3,
+            }));
+
+    let mut shifted = vec![0u64; encoded.len() / 8 + 2];
+    as_bytes_mut(&mut shifted)[4..4 + encoded.len()].copy_from_slice(&encoded);
+    let misaligned = &as_bytes(&shifted)[4..4 + encoded.len()];
+    assert!(MappedNameTable::from_bytes_upon(misaligned,
+                                             StaticNameTable::new_shared(NAME_DATA_1,
+                                                                         INDEX_DATA_1))
+        .err() == Some(FormatError::Misaligned));
+}
@@ -0,0 +1,110 @@
+use nametable::*;
+use std::sync::Arc;
+use std::thread;
+
+static NAME_DATA_1: &str = "FIRSTSECONDTHIRD";
+static INDEX_DATA_1: &[usize] = &[0, 5, 11, 16];
+
+#[test]
+fn test1() {
+    let tbl = Arc::new(ConcurrentNameTable::new_upon(StaticNameTable::new_shared(NAME_DATA_1,
+                                                                                 INDEX_DATA_1)));
+    assert!(tbl.initial_local() == 3);
+    assert!(tbl.intern("SECOND") == 1);
+    assert!(tbl.len() == 3);
+
+    let workers: Vec<_> = (0..8)
+        .map(|worker| {
+            let tbl = tbl.clone();
+            thread::spawn(move || {
+                let mut idxes = Vec::new();
+                for i in 0..2000 {
+                    let name = format!("name{}", (i * 7 + worker * 13) % 2000);
+                    let idx = tbl.intern(&name);
+                    assert!(tbl.at(idx) == name);
+                    idxes.push((name, idx));
+                }
+                idxes
+            })
+        })
+        .collect();
+    let results: Vec<Vec<(String, usize)>> =
+        workers.into_iter().map(|handle| handle.join().unwrap()).collect();
+
+    assert!(tbl.len_local() == 2000);
+    assert!(tbl.len() == 2003);
+    for idxes in results.iter() {
+        for &(ref name, idx) in idxes.iter() {
+            assert!((3..2003).contains(&idx));
+            assert!(tbl.find(name) == Some(idx));
+            assert!(&tbl[idx] == name.as_str());
+        }
+    }
+    let mut names: Vec<&str> = tbl.iter_local().map(|(_, name)| name).collect();
+    names.sort();
+    names.dedup();
+    assert!(names.len() == 2000);
+
+    assert!(tbl.find("THIRD") == Some(2));
+    assert!(tbl.find("UNEXIST").is_none());
+    assert!(tbl.get(2003).is_none());
+}
+
+#[test]
+fn test2() {
+    let tbl = ConcurrentNameTable::new();
+    for i in 0..10000 {
+        assert!(tbl.intern(&i.to_string()) == i);
+    }
+    for i in 0..10000 {
+        assert!(tbl.at(i) == i.to_string());
+        assert!(tbl.intern(&i.to_string()) == i);
+    }
+    assert!(tbl.intern("") == 10000);
+
+    let mut child = DynamicNameTable::new_upon(Arc::new(tbl));
+    assert!(child.intern("9999") == 9999);
+    assert!(child.intern("new") == 10001);
+}
+
+#[test]
+fn test3() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let tbl = Arc::new(ConcurrentNameTable::new());
+    let done = Arc::new(AtomicBool::new(false));
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let tbl = tbl.clone();
+            let done = done.clone();
+            thread::spawn(move || {
+                while !done.load(Ordering::Acquire) {
+                    // The newest index the table reports must already hold
+                    // its name.
+                    let len = tbl.len();
+                    if len > 0 {
+                        assert!(tbl.at(len - 1).starts_with("name"));
+                    }
+                }
+            })
+        })
+        .collect();
+    let writers: Vec<_> = (0..4)
+        .map(|worker| {
+            let tbl = tbl.clone();
+            thread::spawn(move || {
+                for i in 0..1000 {
+                    tbl.intern(&format!("name{}", i * 4 + worker));
+                }
+            })
+        })
+        .collect();
+    for handle in writers {
+        handle.join().unwrap();
+    }
+    done.store(true, Ordering::Release);
+    for handle in readers {
+        handle.join().unwrap();
+    }
+    assert!(tbl.len() == 4000);
+}
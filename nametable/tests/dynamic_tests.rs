@@ -104,3 +104,263 @@ fn test3() {
     assert!(tbl.intern("EIGHTH") == 7);
 
 }
+
+#[test]
+fn test4() {
+    let mut tbl = DynamicNameTable::new_upon(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+
+    for i in 0..10000 {
+        assert!(tbl.intern(&format!("NAME{}", i)) == 3 + i);
+    }
+    assert!(tbl.intern("SECOND") == 1);
+
+    assert!(tbl.len() == 10003);
+    assert!(tbl.len_local() == 10000);
+    for i in 0..10000 {
+        let name = format!("NAME{}", i);
+        assert!(tbl.find_local(&name).unwrap() == i);
+        assert!(tbl.find(&name).unwrap() == 3 + i);
+        assert!(tbl.intern(&name) == 3 + i);
+        assert!(tbl.at(3 + i) == name);
+    }
+    assert!(tbl.find_local("NAME10000").is_none());
+    assert!(tbl.find_local("FIRST").is_none());
+    assert!(tbl.len() == 10003);
+}
+
+#[test]
+fn test5() {
+    let static_tbl = StaticNameTable::new_upon(NAME_DATA_2,
+                                               INDEX_DATA_2,
+                                               StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let mut tbl = DynamicNameTable::new_upon(static_tbl);
+    tbl.intern("EIGHTH");
+    tbl.intern("NINTH");
+
+    let entries: Vec<(usize, &str)> = tbl.iter().collect();
+    assert!(entries.len() == tbl.len());
+    assert!(tbl.iter().len() == 9);
+    assert!(entries[0] == (0, "FIRST"));
+    assert!(entries[3] == (3, "FOURTH"));
+    assert!(entries[8] == (8, "NINTH"));
+    for (idx, name) in tbl.iter() {
+        assert!(tbl.at(idx) == name);
+    }
+
+    let local: Vec<(usize, &str)> = tbl.iter_local().collect();
+    assert!(local == vec![(7, "EIGHTH"), (8, "NINTH")]);
+    assert!(tbl.iter_local().next_back() == Some((8, "NINTH")));
+
+    let layers: Vec<_> = tbl.layers().collect();
+    assert!(layers == vec![7..9, 3..7, 0..3]);
+
+    let parent = tbl.parent().unwrap();
+    assert!(parent.iter().count() == 7);
+    assert!(parent.iter_local().next() == Some((3, "FOURTH")));
+    assert!(parent.layers().collect::<Vec<_>>() == vec![3..7, 0..3]);
+}
+
+#[test]
+fn test6() {
+    let static_tbl = StaticNameTable::new_upon(NAME_DATA_2,
+                                               INDEX_DATA_2,
+                                               StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let mut tbl = DynamicNameTable::new_upon(static_tbl);
+    let eighth = tbl.intern("EIGHTH");
+    let ninth = tbl.intern("NINTH");
+
+    let snapshot = tbl.snapshot();
+    assert!(snapshot.initial_idx == 7);
+    assert!(snapshot.names == vec!["EIGHTH".to_owned(), "NINTH".to_owned()]);
+
+    let parent = StaticNameTable::new_upon(NAME_DATA_2,
+                                           INDEX_DATA_2,
+                                           StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let mut restored = DynamicNameTable::restore_upon(snapshot.clone(), parent).unwrap();
+    assert!(restored.len() == 9);
+    assert!(restored.find("EIGHTH") == Some(eighth));
+    assert!(restored.at(ninth) == "NINTH");
+    assert!(restored.intern("TENTH") == 9);
+
+    let short_parent = StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1);
+    assert!(DynamicNameTable::restore_upon(snapshot.clone(), short_parent).err() ==
+            Some(SnapshotError::InitialMismatch {
+                expected: 3,
+                found: 7,
+            }));
+    assert!(DynamicNameTable::restore(snapshot).is_err());
+
+    let duplicated = DynamicNameTableSnapshot {
+        initial_idx: 3,
+        names: vec!["EIGHTH".to_owned(), "FIRST".to_owned()],
+    };
+    let short_parent = StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1);
+    assert!(DynamicNameTable::restore_upon(duplicated, short_parent).err() ==
+            Some(SnapshotError::DuplicateName("FIRST".to_owned())));
+
+    let root = DynamicNameTableSnapshot {
+        initial_idx: 0,
+        names: vec!["a".to_owned(), "b".to_owned(), "a".to_owned()],
+    };
+    assert!(DynamicNameTable::restore(root).err() ==
+            Some(SnapshotError::DuplicateName("a".to_owned())));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test7() {
+    let mut tbl = DynamicNameTable::new_upon(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    tbl.intern("FOURTH");
+    tbl.intern("FIFTH");
+
+    let json = ::serde_json::to_string(&tbl).unwrap();
+    assert!(json == r#"{"initial_idx":3,"names":["FOURTH","FIFTH"]}"#);
+
+    let snapshot: DynamicNameTableSnapshot = ::serde_json::from_str(&json).unwrap();
+    assert!(::serde_json::to_string(&snapshot).unwrap() == json);
+    let restored =
+        DynamicNameTable::restore_upon(snapshot, StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1))
+            .unwrap();
+    assert!(restored.find("FIFTH") == Some(4));
+    assert!(restored.at(3) == "FOURTH");
+}
+
+#[test]
+fn test8() {
+    let static_tbl = StaticNameTable::new_upon(NAME_DATA_2,
+                                               INDEX_DATA_2,
+                                               StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let mut tbl = DynamicNameTable::new_upon(static_tbl);
+    for i in 0..500 {
+        tbl.intern(&format!("name{}", i));
+    }
+    tbl.intern("");
+    let entries: Vec<(usize, String)> =
+        tbl.iter().map(|(idx, name)| (idx, name.to_owned())).collect();
+
+    let frozen = tbl.freeze();
+    assert!(frozen.initial_local() == 7);
+    assert!(frozen.len_local() == 501);
+    assert!(frozen.len() == 508);
+    assert!(frozen.layers().collect::<Vec<_>>() == vec![7..508, 3..7, 0..3]);
+    for &(idx, ref name) in entries.iter() {
+        assert!(frozen.at(idx) == name);
+        assert!(frozen.find(name) == Some(idx));
+    }
+    assert!(frozen.find_local("FIRST").is_none());
+    assert!(frozen.find("FIRST") == Some(0));
+    assert!(frozen.find("name500").is_none());
+    assert!(&frozen[NameEnum2::FOURTH] == "FOURTH");
+
+    let empty = DynamicNameTable::new().freeze();
+    assert!(empty.len() == 0);
+    assert!(empty.find("").is_none());
+}
+
+#[test]
+fn test9() {
+    let mut tbl = DynamicNameTable::new_upon(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    assert!(tbl.intern("FOURTH") == 3);
+    let start = tbl.checkpoint();
+    assert!(start.index() == 4);
+
+    for i in 0..100 {
+        assert!(tbl.intern(&format!("name{}", i)) == 4 + i);
+    }
+    let middle = tbl.checkpoint();
+    for i in 100..1000 {
+        tbl.intern(&format!("name{}", i));
+    }
+    assert!(tbl.len() == 1004);
+
+    assert!(tbl.rollback(middle).is_ok());
+    assert!(tbl.len() == 104);
+    assert!(tbl.find("name99") == Some(103));
+    assert!(tbl.find("name100").is_none());
+    assert!(tbl.get(104).is_none());
+    assert!(tbl.intern("name500") == 104);
+
+    assert!(tbl.rollback(start).is_ok());
+    assert!(tbl.len() == 4);
+    assert!(tbl.find("FOURTH") == Some(3));
+    assert!(tbl.find("name0").is_none());
+    assert!(tbl.find("FIRST") == Some(0));
+
+    assert!(tbl.rollback(middle) ==
+            Err(RollbackError {
+                mark: 104,
+                initial: 3,
+                end: 4,
+            }));
+    for i in 0..1000 {
+        assert!(tbl.intern(&format!("again{}", i)) == 4 + i);
+    }
+    for i in 0..1000 {
+        assert!(tbl.find(&format!("again{}", i)) == Some(4 + i));
+    }
+
+    let parent_mark = DynamicNameTable::new().checkpoint();
+    assert!(tbl.rollback(parent_mark).is_err());
+    assert!(tbl.len() == 1004);
+}
+
+#[test]
+fn test10() {
+    use std::sync::Arc;
+    use std::thread;
+
+    let root = StaticNameTable::new_shared(NAME_DATA_1, INDEX_DATA_1);
+    let base = Arc::new(StaticNameTable::new_upon_shared(NAME_DATA_2, INDEX_DATA_2, root));
+    let workers: Vec<_> = (0..4)
+        .map(|worker| {
+            let base = base.clone();
+            thread::spawn(move || {
+                let mut tbl = DynamicNameTable::new_upon_shared(base);
+                assert!(tbl.initial_local() == 7);
+                assert!(tbl.intern("FIFTH") == 4);
+                assert!(tbl.intern(&format!("worker{}", worker)) == 7);
+                tbl.snapshot().names
+            })
+        })
+        .collect();
+    for (worker, handle) in workers.into_iter().enumerate() {
+        assert!(handle.join().unwrap() == vec![format!("worker{}", worker)]);
+    }
+    assert!(Arc::strong_count(&base) == 1);
+
+    let leaked: &'static StaticNameTable =
+        Box::leak(Box::new(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1)));
+    let mut first = DynamicNameTable::new_upon(leaked);
+    let mut second = DynamicNameTable::new_upon(leaked);
+    assert!(first.intern("a") == 3);
+    assert!(second.intern("b") == 3);
+    assert!(first.find("THIRD") == Some(2));
+    assert!(second.parent().unwrap().len() == 3);
+}
+
+#[test]
+fn test11() {
+    let mut tbl = DynamicNameTable::new_upon(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    assert!(tbl.raw_parts() == ("", &[0usize][..]));
+
+    assert!(tbl.intern("FOURTH") == 3);
+    assert!(tbl.intern("") == 4);
+    assert!(tbl.intern("FIFTH") == 5);
+    assert!(tbl.intern("FIRST") == 0);
+    assert!(tbl.raw_parts() == ("FOURTHFIFTH", &[0usize, 6, 6, 11][..]));
+
+    let mark = tbl.checkpoint();
+    assert!(tbl.intern("SIXTH") == 6);
+    tbl.rollback(mark).unwrap();
+    assert!(tbl.raw_parts() == ("FOURTHFIFTH", &[0usize, 6, 6, 11][..]));
+    assert!(tbl.intern("SEVENTH") == 6);
+    assert!(&tbl[6] == "SEVENTH");
+
+    let (names, offsets) = tbl.raw_parts();
+    let names: &'static str = Box::leak(names.to_owned().into_boxed_str());
+    let offsets: &'static [usize] = Box::leak(offsets.to_vec().into_boxed_slice());
+    let exported = StaticNameTable::new_upon(names,
+                                             offsets,
+                                             StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    assert!(exported.iter().eq(tbl.iter()));
+}
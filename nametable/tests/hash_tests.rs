@@ -29,42 +29,41 @@ impl NameTableIdx for NameEnum2 {
 
 static NAME_DATA_1: &'static str = "FIRSTSECONDTHIRD";
 static INDEX_DATA_1: &'static [usize] = &[0, 5, 11, 16];
-static HASH_DATA_1: &'static [(u64, usize)] = &[];
+static HASHES_1: &'static [u64] = &[];
+static HASH_IDXES_1: &'static [usize] = &[];
 
 static NAME_DATA_2: &'static str = "FOURTHFIFTHSIXTHSEVENTH";
 static INDEX_DATA_2: &'static [usize] = &[0, 6, 11, 16, 23];
-static mut HASH_DATA_HOLDER_2: [(u64, usize); 4] = [(0, 0), (0, 1), (0, 2), (0, 3)];
+static HASH_DATA_HOLDER_2: OnceLock<([u64; 4], [usize; 4])> = OnceLock::new();
 
-use std::sync::{Once, ONCE_INIT};
-
-static START: Once = ONCE_INIT;
+use std::sync::OnceLock;
 
 #[test]
 fn test1() {
-    START.call_once(|| {
-        unsafe {
-            HASH_DATA_HOLDER_2[0].0 = name_hash("FOURTH");
-            HASH_DATA_HOLDER_2[1].0 = name_hash("FIFTH");
-            HASH_DATA_HOLDER_2[2].0 = name_hash("SIXTH");
-            HASH_DATA_HOLDER_2[3].0 = name_hash("SEVENTH");
-            HASH_DATA_HOLDER_2.sort_by(|&(a, _), &(b, _)| a.cmp(&b));
-        }
+    let hash_data_2: &'static ([u64; 4], [usize; 4]) = HASH_DATA_HOLDER_2.get_or_init(|| {
+        let mut hash_data = [(name_hash("FOURTH"), 0),
+                             (name_hash("FIFTH"), 1),
+                             (name_hash("SIXTH"), 2),
+                             (name_hash("SEVENTH"), 3)];
+        hash_data.sort_by_key(|&(a, _)| a);
+        (hash_data.map(|(hash, _)| hash), hash_data.map(|(_, idx)| idx))
     });
-    let hash_data_2: &'static [(u64, usize)] = unsafe { &HASH_DATA_HOLDER_2 };
 
     let tbl = StaticHashedNameTable::new_upon(NAME_DATA_2,
                                               INDEX_DATA_2,
-                                              hash_data_2,
+                                              &hash_data_2.0,
+                                              &hash_data_2.1,
                                               StaticHashedNameTable::new(NAME_DATA_1,
                                                                          INDEX_DATA_1,
-                                                                         HASH_DATA_1));
+                                                                         HASHES_1,
+                                                                         HASH_IDXES_1));
 
-    assert!(hash_data_2.len() == 4);
+    assert!(hash_data_2.0.len() == 4);
     let fourth_hash = name_hash("FOURTH");
-    let result = hash_data_2.binary_search_by(|&(a, _)| a.cmp(&fourth_hash));
+    let result = hash_data_2.0.binary_search(&fourth_hash);
     assert!(result.is_ok());
     if let Ok(val) = result {
-        assert!(tbl.at_local(hash_data_2[val].1) == "FOURTH");
+        assert!(tbl.at_local(hash_data_2.1[val]) == "FOURTH");
     }
 
 
@@ -99,6 +98,96 @@ fn test1() {
     assert!(tbl.index(NameEnum1::FIRST) == "FIRST");
     assert!(tbl.index(NameEnum2::FOURTH) == "FOURTH");
 
+    assert!(tbl.try_index(NameEnum1::SECOND) == Some("SECOND"));
+    assert!(tbl.try_index(NameEnum2::SIXTH) == Some("SIXTH"));
+    assert!(tbl.try_index(7).is_none());
+
+
+
+}
+
+#[test]
+fn test2() {
+    assert!(NAME_HASH_ALGORITHM == "fnv1a-64");
+    assert!(name_hash("") == 0xcbf29ce484222325);
+    assert!(name_hash("a") == 0xaf63dc4c8601ec8c);
+    assert!(name_hash("foobar") == 0x85944171f73967e8);
+
+    let tbl = StaticHashedNameTable::new(NAME_DATA_1, INDEX_DATA_1, HASHES_1, HASH_IDXES_1)
+        .with_hash_version(NAME_HASH_VERSION);
+    assert!(tbl.find("SECOND").unwrap() == 1);
+}
+
+#[test]
+#[should_panic(expected = "regenerate the table")]
+fn test3() {
+    let _ = StaticHashedNameTable::new(NAME_DATA_1, INDEX_DATA_1, HASHES_1, HASH_IDXES_1)
+        .with_hash_version(NAME_HASH_VERSION + 1);
+}
+
+#[test]
+fn test4() {
+    let perfect_hash: &'static PerfectHashBuf =
+        Box::leak(Box::new(PerfectHashBuf::build(&["FOURTH", "FIFTH", "SIXTH", "SEVENTH"])));
+    assert!(perfect_hash.map().len() == 4);
+
+    let tbl = StaticHashedNameTable::new_upon(NAME_DATA_2,
+                                              INDEX_DATA_2,
+                                              &[],
+                                              &[],
+                                              StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1))
+        .with_perfect_hash(perfect_hash.as_perfect_hash());
+
+    assert!(tbl.find_local("FOURTH").unwrap() == 0);
+    assert!(tbl.find_local("FIFTH").unwrap() == 1);
+    assert!(tbl.find_local("SIXTH").unwrap() == 2);
+    assert!(tbl.find_local("SEVENTH").unwrap() == 3);
+    assert!(tbl.find_local("FIRST").is_none());
+    assert!(tbl.find_local("UNEXIST").is_none());
+    assert!(tbl.find_local("").is_none());
+
+    assert!(tbl.find("FIRST").unwrap() == 0);
+    assert!(tbl.find("SEVENTH").unwrap() == 6);
+    assert!(tbl.find("UNEXIST").is_none());
+}
+
+#[test]
+fn test5() {
+    let names: Vec<String> = (0..5000).map(|i| format!("name{}", i)).collect();
+    let name_refs: Vec<&str> = names.iter().map(|name| name.as_str()).collect();
+    let perfect_hash = PerfectHashBuf::build(&name_refs);
+    let perfect_hash = perfect_hash.as_perfect_hash();
+
+    let mut seen = vec![false; names.len()];
+    for (idx, name) in name_refs.iter().enumerate() {
+        assert!(perfect_hash.find(name).unwrap() == idx);
+        seen[idx] = true;
+    }
+    assert!(seen.iter().all(|&x| x));
 
+    let duplicated = PerfectHashBuf::build(&["A", "B", "A", "C"]);
+    assert!(duplicated.map().len() == 3);
+    assert!(duplicated.as_perfect_hash().find("A").unwrap() == 0);
+    assert!(duplicated.as_perfect_hash().find("C").unwrap() == 3);
 
+    let empty = PerfectHashBuf::build(&[]);
+    assert!(empty.as_perfect_hash().find("A").is_none());
+}
+
+#[test]
+fn test6() {
+    // Pretend "FIFTH" collides with "FOURTH": the lookup must check every
+    // candidate sharing the hash, not just the first one it lands on.
+    let hashes: &'static [u64] = Box::leak(vec![name_hash("FOURTH"); 2].into_boxed_slice());
+    let tbl = StaticHashedNameTable::new(NAME_DATA_2, INDEX_DATA_2, hashes, &[1, 0]);
+
+    assert!(tbl.find_local("FOURTH").unwrap() == 0);
+    assert!(tbl.find_local("SEVENTH").is_none());
+    assert!(tbl.find_local("UNEXIST").is_none());
+}
+
+#[test]
+#[should_panic(expected = "the same length")]
+fn test7() {
+    let _ = StaticHashedNameTable::new(NAME_DATA_1, INDEX_DATA_1, &[0], HASH_IDXES_1);
 }
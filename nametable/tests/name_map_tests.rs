@@ -0,0 +1,60 @@
+use nametable::*;
+
+static NAME_DATA_1: &str = "FIRSTSECONDTHIRD";
+static INDEX_DATA_1: &[usize] = &[0, 5, 11, 16];
+
+#[repr(usize)]
+#[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
+enum NameEnum1 {
+    FIRST = 0,
+    SECOND = 1,
+    THIRD = 2,
+}
+
+impl NameTableIdx for NameEnum1 {
+    fn to_index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[test]
+fn test1() {
+    let mut tbl = DynamicNameTable::new_upon(StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let fourth = tbl.intern("FOURTH");
+
+    let mut map = NameMap::for_table(&tbl);
+    assert!(map.is_empty());
+    assert!(map.insert(NameEnum1::SECOND, 2).is_none());
+    assert!(map.insert(fourth, 4).is_none());
+    assert!(map.insert(NameEnum1::SECOND, 20) == Some(2));
+    assert!(map.len() == 2);
+
+    assert!(map[NameEnum1::SECOND] == 20);
+    assert!(map[3] == 4);
+    assert!(map.get(NameEnum1::FIRST).is_none());
+    assert!(map.get(100).is_none());
+    assert!(map.get_by_name(&tbl, "FOURTH") == Some(&4));
+    assert!(map.get_by_name(&tbl, "THIRD").is_none());
+    assert!(map.get_by_name(&tbl, "UNEXIST").is_none());
+
+    map[fourth] += 1;
+    assert!(map.iter().collect::<Vec<_>>() == vec![(1, &20), (3, &5)]);
+
+    assert!(map.remove(NameEnum1::SECOND) == Some(20));
+    assert!(map.remove(NameEnum1::SECOND).is_none());
+    assert!(map.remove(100).is_none());
+    assert!(map.len() == 1);
+    assert!(!map.contains(1));
+    assert!(map.contains(3));
+}
+
+#[test]
+#[should_panic(expected = "index 2 has no value in this map")]
+fn test2() {
+    let map: NameMap<&str> = vec![(NameEnum1::FIRST, "first"), (NameEnum1::SECOND, "second")]
+        .into_iter()
+        .collect();
+    assert!(map[NameEnum1::SECOND] == "second");
+    let _ = map[NameEnum1::THIRD];
+}
@@ -69,6 +69,7 @@ fn test1() {
 
 
 #[test]
+#[cfg(feature = "alloc")]
 fn test2() {
     let tbl = StaticNameTable::new_upon(NAME_DATA_2,
                                         INDEX_DATA_2,
@@ -105,3 +106,82 @@ fn test2() {
     assert!(&tbl[NameEnum1::FIRST] == "FIRST");
     assert!(&tbl[NameEnum2::FOURTH] == "FOURTH");
 }
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test3() {
+    let tbl = StaticNameTable::new_upon(NAME_DATA_2,
+                                        INDEX_DATA_2,
+                                        StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+
+    assert!(tbl.get(0) == Some("FIRST"));
+    assert!(tbl.get(3) == Some("FOURTH"));
+    assert!(tbl.get(6) == Some("SEVENTH"));
+    assert!(tbl.get(7).is_none());
+    assert!(tbl.get(usize::MAX).is_none());
+
+    assert!(tbl.get_local(0) == Some("FOURTH"));
+    assert!(tbl.get_local(3) == Some("SEVENTH"));
+    assert!(tbl.get_local(4).is_none());
+
+    let upper = StaticNameTable::new_upon(NAME_DATA_1, INDEX_DATA_1, tbl);
+    assert!(upper.get(7) == Some("FIRST"));
+    assert!(upper.get(10).is_none());
+}
+
+#[test]
+#[should_panic(expected = "index 7 is out of bound for the range 0..7 of a table 2 layer(s) deep")]
+#[cfg(feature = "alloc")]
+fn test4() {
+    let tbl = StaticNameTable::new_upon(NAME_DATA_2,
+                                        INDEX_DATA_2,
+                                        StaticNameTable::new(NAME_DATA_1, INDEX_DATA_1));
+    let _ = &tbl[7];
+}
+
+#[cfg(feature = "alloc")]
+static INDEX_DATA_1_U16: &'static [u16] = &[0, 5, 11, 16];
+#[cfg(feature = "alloc")]
+static INDEX_DATA_2_U8: &'static [u8] = &[0, 6, 11, 16, 23];
+#[cfg(feature = "alloc")]
+static HASHES_2: &'static [u64] = &[];
+#[cfg(feature = "alloc")]
+static HASH_IDXES_2_U8: &'static [u8] = &[];
+
+#[test]
+#[cfg(feature = "alloc")]
+fn test5() {
+    let tbl = StaticHashedNameTable::new_upon(NAME_DATA_2,
+                                              INDEX_DATA_2_U8,
+                                              HASHES_2,
+                                              HASH_IDXES_2_U8,
+                                              StaticNameTable::new(NAME_DATA_1,
+                                                                   INDEX_DATA_1_U16));
+
+    assert!(tbl.len() == 7);
+    assert!(&tbl[NameEnum1::SECOND] == "SECOND");
+    assert!(&tbl[NameEnum2::SIXTH] == "SIXTH");
+    assert!(&tbl[6] == "SEVENTH");
+    assert!(tbl.find("THIRD").unwrap() == 2);
+    assert!(tbl.find("FIFTH").unwrap() == 4);
+    assert!(tbl.find("UNEXIST").is_none());
+}
+
+static ROOT_1: StaticNameTable<usize, dyn NameTable + Send + Sync> =
+    StaticNameTable::new_shared(NAME_DATA_1, INDEX_DATA_1);
+static LAYERED_2: StaticNameTable<usize, dyn NameTable + Send + Sync> =
+    StaticNameTable::new_upon_static(NAME_DATA_2, INDEX_DATA_2, 3, &ROOT_1);
+
+#[test]
+fn test6() {
+    assert!(LAYERED_2.len() == 7);
+    assert!(&LAYERED_2[NameEnum1::SECOND] == "SECOND");
+    assert!(&LAYERED_2[NameEnum1::THIRD] == "THIRD");
+    assert!(&LAYERED_2[NameEnum2::FOURTH] == "FOURTH");
+    assert!(LAYERED_2.find("SECOND").unwrap() == 1);
+    assert!(LAYERED_2.find("SEVENTH").unwrap() == 6);
+
+    let upper = StaticNameTable::new_upon_static(NAME_DATA_1, INDEX_DATA_1, 7, &LAYERED_2);
+    assert!(upper.find("FOURTH").unwrap() == 3);
+    assert!(&upper[7] == "FIRST");
+}
@@ -1,7 +1,20 @@
 #![allow(dead_code)]
 
 extern crate nametable;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 
+#[allow(clippy::redundant_static_lifetimes, clippy::upper_case_acronyms)]
 mod static_tests;
+#[cfg(feature = "alloc")]
+#[allow(clippy::redundant_static_lifetimes, clippy::upper_case_acronyms)]
 mod dynamic_tests;
+#[cfg(feature = "alloc")]
+#[allow(clippy::redundant_static_lifetimes, clippy::upper_case_acronyms)]
 mod hash_tests;
+#[cfg(feature = "alloc")]
+mod binary_tests;
+#[cfg(feature = "std")]
+mod concurrent_tests;
+#[cfg(feature = "alloc")]
+mod name_map_tests;
@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+#[cfg(any(feature = "toml", feature = "json"))]
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+#[cfg(any(feature = "toml", feature = "json"))]
+use serde::Deserialize;
+#[cfg(any(feature = "toml", feature = "json"))]
+use std::fmt;
+
+use crate::spec::{variant_name, Error, TableSpec};
+
+impl TableSpec {
+    /// Loads a table definition from a data file, picking the format from the
+    /// file extension:
+    ///
+    /// * `.toml` (with the `toml` feature) and `.json` (with the `json`
+    ///   feature) hold an optional `name` and `base`, and a list of `entries`,
+    ///   each with a `key` and an optional `value` that defaults to the key.
+    /// * Anything else is read as plain text with one string per line, with
+    ///   variant names derived from the strings as in `from_layer`, so lines
+    ///   such as `fn` or `E-0001` are fine. Blank lines and lines starting
+    ///   with `#` are skipped; a leading `\` is dropped, so `\#include` is the
+    ///   string `#include`.
+    ///
+    /// The table is named after the file stem unless the file names it.
+    /// `write_to_out_dir` tells cargo to rerun the build script when the file
+    /// changes.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TableSpec, Error> {
+        let path = path.as_ref();
+        let source =
+            fs::read_to_string(path).map_err(|err| file_error(path, None, err.to_string()))?;
+        let default_name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        let mut spec = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => from_toml(path, &source, default_name)?,
+            Some("json") => from_json(path, &source, default_name)?,
+            _ => from_text(path, &source, default_name)?,
+        };
+        spec.source_path = Some(path.to_owned());
+        Ok(spec)
+    }
+}
+
+fn file_error(path: &Path, line: Option<usize>, message: String) -> Error {
+    Error::File {
+        path: path.to_owned(),
+        line,
+        message,
+    }
+}
+
+/// Checks what `nametable!` would reject, so that the error can point at the
+/// offending line instead of surfacing when the table is generated.
+#[cfg(any(feature = "toml", feature = "json"))]
+fn check_entry(key: &str, value: &str, owners: &mut HashMap<String, String>) -> Result<(), String> {
+    if syn::parse_str::<syn::Ident>(key).is_err() {
+        return Err(format!("`{}` is not a valid entry name", key));
+    }
+    if let Some(owner) = owners.get(value) {
+        return Err(format!(
+            "string {:?} of `{}` is already used by `{}`",
+            value, key, owner
+        ));
+    }
+    owners.insert(value.to_owned(), key.to_owned());
+    Ok(())
+}
+
+fn from_text(path: &Path, source: &str, default_name: &str) -> Result<TableSpec, Error> {
+    let mut spec = TableSpec::new(default_name);
+    let mut owners = HashMap::new();
+    let mut used = HashSet::new();
+    for (line_idx, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let value = line.strip_prefix('\\').unwrap_or(line);
+        if let Some(owner) = owners.get(value) {
+            let message = format!("string {:?} is already used by `{}`", value, owner);
+            return Err(file_error(path, Some(line_idx + 1), message));
+        }
+        let key = variant_name(value, &mut used);
+        spec = spec.entry(&key, value);
+        owners.insert(value.to_owned(), key);
+    }
+    Ok(spec)
+}
+
+#[cfg(feature = "toml")]
+fn from_toml(path: &Path, source: &str, default_name: &str) -> Result<TableSpec, Error> {
+    let table: FileTable = toml::from_str(source).map_err(|err| {
+        let line = err
+            .span()
+            .map(|span| source[..span.start].matches('\n').count() + 1);
+        file_error(path, line, err.message().to_owned())
+    })?;
+    Ok(table.into_spec(default_name))
+}
+
+#[cfg(not(feature = "toml"))]
+fn from_toml(path: &Path, _source: &str, _default_name: &str) -> Result<TableSpec, Error> {
+    Err(file_error(
+        path,
+        None,
+        "reading TOML files requires the `toml` feature of nametable_codegen".to_owned(),
+    ))
+}
+
+#[cfg(feature = "json")]
+fn from_json(path: &Path, source: &str, default_name: &str) -> Result<TableSpec, Error> {
+    let table: FileTable = serde_json::from_str(source).map_err(|err| {
+        if err.line() == 0 {
+            return file_error(path, None, err.to_string());
+        }
+        let position = format!(" at line {} column {}", err.line(), err.column());
+        let message = err.to_string();
+        let message = message.trim_end_matches(position.as_str()).to_owned();
+        file_error(path, Some(err.line()), message)
+    })?;
+    Ok(table.into_spec(default_name))
+}
+
+#[cfg(not(feature = "json"))]
+fn from_json(path: &Path, _source: &str, _default_name: &str) -> Result<TableSpec, Error> {
+    Err(file_error(
+        path,
+        None,
+        "reading JSON files requires the `json` feature of nametable_codegen".to_owned(),
+    ))
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileTable {
+    name: Option<String>,
+    base: Option<String>,
+    #[serde(default)]
+    entries: FileEntries,
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl FileTable {
+    fn into_spec(self, default_name: &str) -> TableSpec {
+        let mut spec = TableSpec::new(self.name.as_deref().unwrap_or(default_name));
+        if let Some(ref base) = self.base {
+            spec = spec.base(base);
+        }
+        for (key, value) in self.entries.0 {
+            spec = spec.entry(&key, &value);
+        }
+        spec
+    }
+}
+
+/// Entries are checked while they are deserialized, so that the format's own
+/// error reporting attaches the position of the offending entry.
+#[cfg(any(feature = "toml", feature = "json"))]
+#[derive(Default)]
+struct FileEntries(Vec<(String, String)>);
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl<'de> Deserialize<'de> for FileEntries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(EntriesVisitor)
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+struct EntriesVisitor;
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl<'de> Visitor<'de> for EntriesVisitor {
+    type Value = FileEntries;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of entries")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<FileEntries, A::Error> {
+        let mut entries = Vec::new();
+        let mut owners = HashMap::new();
+        while let Some(entry) = seq.next_element_seed(EntrySeed {
+            owners: &mut owners,
+        })? {
+            entries.push(entry);
+        }
+        Ok(FileEntries(entries))
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+struct EntrySeed<'a> {
+    owners: &'a mut HashMap<String, String>,
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl<'de, 'a> DeserializeSeed<'de> for EntrySeed<'a> {
+    type Value = (String, String);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl<'de, 'a> Visitor<'de> for EntrySeed<'a> {
+    type Value = (String, String);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an entry with a `key` and an optional `value`")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        const FIELDS: &[&str] = &["key", "value"];
+        let mut key: Option<String> = None;
+        let mut value: Option<String> = None;
+        while let Some(field) = map.next_key::<String>()? {
+            match field.as_str() {
+                "key" => key = Some(map.next_value()?),
+                "value" => value = Some(map.next_value()?),
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+        let key = key.ok_or_else(|| de::Error::missing_field("key"))?;
+        let value = value.unwrap_or_else(|| key.clone());
+        check_entry(&key, &value, self.owners).map_err(de::Error::custom)?;
+        Ok((key, value))
+    }
+}
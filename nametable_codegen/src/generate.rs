@@ -0,0 +1,306 @@
+use nametable::{PerfectHashBuf, NAME_HASH_ALGORITHM, NAME_HASH_VERSION};
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Path, PathSegment, Token};
+
+use crate::parse::Table;
+
+/// Base paths are written relative to the module containing the table
+/// definition, but used from inside the generated module, one level deeper.
+fn path_from_generated_module(path: &Path) -> Path {
+    let mut result = path.clone();
+    if path.leading_colon.is_some() {
+        return result;
+    }
+    let first = &path.segments[0].ident;
+    if first == "crate" {
+        return result;
+    }
+    let super_segment = PathSegment::from(Ident::new("super", first.span()));
+    if first == "self" {
+        result.segments[0] = super_segment;
+    } else {
+        result.segments.insert(0, super_segment);
+    }
+    result
+}
+
+/// `Names` always derives `Copy` and `Clone`; deriving them again would be
+/// a conflicting implementation, so they are dropped from the user's list.
+fn without_builtin_derives(attr: &Attribute) -> Option<TokenStream> {
+    let derives = match attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated) {
+        Ok(derives) => derives,
+        Err(_) => return Some(quote!(#attr)),
+    };
+    let derives: Vec<&Path> = derives
+        .iter()
+        .filter(|path| {
+            let last = &path.segments[path.segments.len() - 1].ident;
+            last != "Copy" && last != "Clone"
+        })
+        .collect();
+    if derives.is_empty() {
+        None
+    } else {
+        Some(quote!(#[derive(#(#derives),*)]))
+    }
+}
+
+pub fn generate_nametable_item(table: &Table) -> TokenStream {
+    let artifact_name = &table.name;
+
+    // Doc comments and `cfg` describe the table as a whole and go on the
+    // module; everything else (derives, serde attributes, ...) is about the
+    // values and goes on `Names`.
+    let mut mod_attrs = Vec::new();
+    let mut enum_attrs = Vec::new();
+    for attr in table.attrs.iter() {
+        if attr.path().is_ident("doc") || attr.path().is_ident("cfg") {
+            mod_attrs.push(quote!(#attr));
+        } else if attr.path().is_ident("derive") {
+            enum_attrs.extend(without_builtin_derives(attr));
+        } else {
+            enum_attrs.push(quote!(#attr));
+        }
+    }
+    let base_artifact_path = table.base.as_ref().map(path_from_generated_module);
+    let offset = table
+        .offset
+        .clone()
+        .unwrap_or_else(|| Ident::new("usize", Span::call_site()));
+
+    let initial = match base_artifact_path {
+        Some(ref path) => quote!(#path::INITIAL + #path::COUNT),
+        None => quote!(0usize),
+    };
+    let count = Literal::usize_unsuffixed(table.entries.len());
+    // A table reproducing an existing layer must line up with it, or every
+    // index the layer handed out would name a different entry.
+    let initial_check = table.expected_initial.map(|expected| {
+        let message = format!(
+            "nametable `{}` must start at index {} like the layer it was built from; \
+             check its base",
+            artifact_name, expected
+        );
+        let expected = Literal::usize_unsuffixed(expected);
+        quote!(const _: () = assert!(INITIAL == #expected, #message);)
+    });
+
+    let variants = table.entries.iter().enumerate().map(|(idx, entry)| {
+        let attrs = &entry.attrs;
+        let key = &entry.key;
+        if idx == 0 {
+            quote!(#(#attrs)* #key = INITIAL)
+        } else {
+            let idx = Literal::usize_unsuffixed(idx);
+            quote!(#(#attrs)* #key = INITIAL + #idx)
+        }
+    });
+
+    let keys = table.entries.iter().map(|entry| &entry.key);
+
+    let mut name_data = String::new();
+    let mut index_data = vec![0usize];
+    for entry in table.entries.iter() {
+        name_data.push_str(&entry.value);
+        index_data.push(name_data.len());
+    }
+
+    let index_data = index_data.into_iter().map(Literal::usize_unsuffixed);
+
+    let values: Vec<&str> = table.entries.iter().map(|entry| entry.value.as_str()).collect();
+    let perfect_hash = PerfectHashBuf::build(&values);
+    let perfect_hash_seed = Literal::u64_unsuffixed(perfect_hash.seed());
+    let perfect_hash_displacements = perfect_hash.displacements().iter().map(|&[d1, d2]| {
+        let d1 = Literal::u32_unsuffixed(d1);
+        let d2 = Literal::u32_unsuffixed(d2);
+        quote!([#d1, #d2])
+    });
+    let perfect_hash_map = perfect_hash.map().iter().cloned().map(Literal::usize_unsuffixed);
+
+    let hash_version = Literal::u32_unsuffixed(NAME_HASH_VERSION);
+
+    // Payload types and expressions are written in the module containing the
+    // table definition, so the generated module sees everything its parent
+    // does; its own items take precedence over the glob import.
+    let (payload_scope, payload_items) = match table.payload_type {
+        Some(ref payload_type) => {
+            let payloads = table.entries.iter().map(|entry| &entry.payload);
+            (
+                quote!(
+                    use super::*;
+                ),
+                quote! {
+                    /// Every entry's value, in declaration order.
+                    pub static VALUES: [#payload_type; COUNT] = [#(#payloads),*];
+
+                    impl Names {
+                        /// The value given to this entry after `=>`.
+                        pub fn value(self) -> &'static #payload_type {
+                            &VALUES[self as usize - INITIAL]
+                        }
+                    }
+                },
+            )
+        }
+        None => (TokenStream::new(), TokenStream::new()),
+    };
+
+    // Layered tables borrow the base's `SHARED` static, and plain ones its
+    // `SHARED_PLAIN`, so constructing them never allocates and works without
+    // `alloc`, and a plain chain stays plain all the way down. Hashed tables
+    // always look names up through `PERFECT_HASH`, so they get no sorted hash
+    // index.
+    let (hashed_table, plain_table) = match base_artifact_path {
+        Some(ref path) => (
+            quote!(StaticHashedNameTable::new_upon_static(
+                NAME_DATA, INDEX_DATA, &[], &[], INITIAL, &#path::SHARED)),
+            quote!(StaticNameTable::new_upon_static(
+                NAME_DATA, INDEX_DATA, INITIAL, &#path::SHARED_PLAIN)),
+        ),
+        None => (
+            quote!(StaticHashedNameTable::new_shared(NAME_DATA, INDEX_DATA, &[], &[])),
+            quote!(StaticNameTable::new_shared(NAME_DATA, INDEX_DATA)),
+        ),
+    };
+
+    quote! {
+        #(#mod_attrs)*
+        #[allow(dead_code)]
+        #[allow(unused_imports)]
+        #[allow(deprecated)]
+        // Entries without `for` stand for their own name, which is often lowercase.
+        #[allow(non_camel_case_types)]
+        pub mod #artifact_name {
+            use ::nametable::{
+                NameTable, StaticNameTable, StaticHashedNameTable, NameTableIdx, Offset,
+                PerfectHash};
+            #payload_scope
+
+            pub const INITIAL: usize = #initial;
+            pub const COUNT: usize = #count;
+            #initial_check
+
+            #(#enum_attrs)*
+            #[repr(usize)]
+            #[derive(Copy, Clone)]
+            pub enum Names {
+                #(#variants,)*
+            }
+
+            impl NameTableIdx for Names {
+                fn to_index(&self) -> usize {
+                    *self as usize
+                }
+            }
+
+            // Outside `Names`, so that entries named `ALL` or `from_index`
+            // cannot shadow them.
+            const VARIANTS: [Names; COUNT] = [#(Names::#keys),*];
+
+            fn from_index(idx: usize) -> Option<Names> {
+                match idx.checked_sub(INITIAL) {
+                    Some(local) if local < COUNT => Some(VARIANTS[local]),
+                    _ => None,
+                }
+            }
+
+            impl Names {
+                /// Every variant, in declaration order.
+                pub const ALL: [Names; COUNT] = VARIANTS;
+
+                pub fn as_str(self) -> &'static str {
+                    let idx = self as usize - INITIAL;
+                    &NAME_DATA[INDEX_DATA[idx].to_usize()..INDEX_DATA[idx + 1].to_usize()]
+                }
+
+                /// Returns the variant for table index `idx`, if it is in
+                /// `INITIAL..INITIAL + COUNT`.
+                pub fn from_index(idx: usize) -> Option<Names> {
+                    from_index(idx)
+                }
+            }
+
+            /// Iterates over every variant, in declaration order.
+            pub fn iter() -> ::core::iter::Copied<::core::slice::Iter<'static, Names>> {
+                let all: &'static [Names; COUNT] = &VARIANTS;
+                all.iter().copied()
+            }
+
+            impl ::core::str::FromStr for Names {
+                type Err = ::nametable::UnknownName;
+
+                fn from_str(name: &str) -> Result<Names, ::nametable::UnknownName> {
+                    match PERFECT_HASH.find(name) {
+                        Some(idx) if VARIANTS[idx].as_str() == name => Ok(VARIANTS[idx]),
+                        _ => Err(::nametable::UnknownName),
+                    }
+                }
+            }
+
+            impl ::core::convert::TryFrom<usize> for Names {
+                type Error = ::nametable::IndexOutOfRange;
+
+                fn try_from(idx: usize) -> Result<Names, ::nametable::IndexOutOfRange> {
+                    from_index(idx).ok_or(::nametable::IndexOutOfRange {
+                        index: idx,
+                        initial: INITIAL,
+                        count: COUNT,
+                    })
+                }
+            }
+
+            #payload_items
+
+            const NAME_DATA: &str = #name_data;
+            const INDEX_DATA: &[#offset] = &[#(#index_data),*];
+            const PERFECT_HASH: PerfectHash<'static, #offset> = PerfectHash {
+                seed: #perfect_hash_seed,
+                displacements: &[#(#perfect_hash_displacements),*],
+                map: &[#(#perfect_hash_map),*],
+            };
+
+            pub const HASH_ALGORITHM: &str = #NAME_HASH_ALGORITHM;
+            pub const HASH_VERSION: u32 = #hash_version;
+
+            const _: () = assert!(
+                HASH_VERSION == ::nametable::NAME_HASH_VERSION,
+                "nametable: this table was generated for a different name_hash version; \
+                 nametable_codegen and nametable must agree");
+
+            pub fn new() -> StaticHashedNameTable<#offset, dyn NameTable + Send + Sync> {
+                #hashed_table
+                    .with_hash_version(HASH_VERSION)
+                    .with_perfect_hash(PERFECT_HASH)
+            }
+
+            #[doc(hidden)]
+            pub static SHARED: StaticHashedNameTable<#offset, dyn NameTable + Send + Sync> =
+                #hashed_table.with_perfect_hash(PERFECT_HASH);
+
+            /// The table `new()` returns, as a `static` shared by the whole
+            /// program. Tables layered upon this one build on it too.
+            pub fn shared() -> &'static StaticHashedNameTable<#offset, dyn NameTable + Send + Sync> {
+                &SHARED
+            }
+
+            #[doc(hidden)]
+            pub static SHARED_PLAIN: StaticNameTable<#offset, dyn NameTable + Send + Sync> = #plain_table;
+
+            pub fn new_plain() -> StaticNameTable<#offset, dyn NameTable + Send + Sync> {
+                #plain_table
+            }
+
+            ::nametable::alloc_items! {
+                pub fn new_dynamic() -> ::nametable::DynamicNameTable {
+                    ::nametable::DynamicNameTable::new_upon(shared())
+                }
+
+                pub fn new_dynamic_plain() -> ::nametable::DynamicNameTable {
+                    ::nametable::DynamicNameTable::new_upon(new_plain())
+                }
+            }
+        }
+    }
+}
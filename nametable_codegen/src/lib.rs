@@ -1,12 +1,52 @@
-extern crate syntex;
-extern crate syntex_syntax as syntax;
+//! Code generation for `nametable`.
+//!
+//! This crate turns table definitions into Rust modules built around the
+//! `nametable` runtime types. Most users want the `nametable!` macro from
+//! `nametable_macros`, which is a thin wrapper around `expand`. Build scripts
+//! that assemble their tables from other sources can describe them with
+//! `TableSpec`, or load them from data files with `TableSpec::from_file`, and
+//! write the generated source to `OUT_DIR` instead.
+
 extern crate nametable;
-extern crate quasi;
+extern crate prettyplease;
+extern crate proc_macro2;
+extern crate quote;
+extern crate syn;
+
+mod data_file;
+mod generate;
+mod parse;
+mod spec;
+
+use proc_macro2::TokenStream;
+
+pub use spec::{generate_source, write_to_out_dir, Error, TableSpec};
+
+/// Expands the body of a `nametable!` invocation into one module per table.
+///
+/// Parse errors and invalid tables are reported as `compile_error!`
+/// invocations spanning the offending tokens.
+pub fn expand(input: TokenStream) -> TokenStream {
+    match syn::parse2::<parse::NametableDefs>(input) {
+        Ok(defs) => defs.tables.iter().map(generate::generate_nametable_item).collect(),
+        Err(err) => err.to_compile_error(),
+    }
+}
 
-include!(concat!(env!("OUT_DIR"), "/nametable_codegen.rs"));
+#[cfg(test)]
+mod tests {
+    use quote::quote;
 
-use syntex::Registry;
+    use super::expand;
 
-pub fn register(reg: &mut Registry) {
-    reg.add_macro("nametable", expand);
+    #[test]
+    fn test_duplicate_strings() {
+        let output = expand(quote! {
+            nametable t { A for "x", B for "x" }
+        })
+        .to_string();
+        assert!(output.contains("compile_error"));
+        assert!(output.contains("`B`"));
+        assert!(output.contains("`A`"));
+    }
 }
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, Attribute, Error, Expr, Ident, Lit, Path, Result, Token, Type};
+
+/// The contents of a `nametable!` invocation: any number of tables.
+pub struct NametableDefs {
+    pub tables: Vec<Table>,
+}
+
+/// `#[attrs] nametable name : base { #[attrs] Key, Key for "string" => value, ... }`
+pub struct Table {
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub base: Option<Path>,
+    pub entries: Vec<Entry>,
+    /// The integer type of the generated offsets and indices, from
+    /// `#[nametable(offset = u32)]`; `usize` when absent.
+    pub offset: Option<Ident>,
+    /// The type of the entries' payloads, from `#[nametable(value = Type)]`.
+    pub payload_type: Option<Type>,
+    /// The `INITIAL` the table must end up with, for tables built from an
+    /// existing layer by `TableSpec::from_layer`.
+    pub expected_initial: Option<usize>,
+}
+
+pub struct Entry {
+    pub attrs: Vec<Attribute>,
+    pub key: Ident,
+    pub value: String,
+    pub value_span: Span,
+    /// The expression after `=>`, if any.
+    pub payload: Option<Expr>,
+}
+
+impl Parse for NametableDefs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut tables = Vec::new();
+        while !input.is_empty() {
+            tables.push(input.parse()?);
+        }
+        Ok(NametableDefs { tables })
+    }
+}
+
+impl Parse for Table {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut attrs = input.call(Attribute::parse_outer)?;
+
+        let keyword: Ident = input
+            .parse()
+            .map_err(|err| Error::new(err.span(), "expected keyword `nametable` here"))?;
+        if keyword != "nametable" {
+            return Err(Error::new(keyword.span(), "expected keyword `nametable` here"));
+        }
+
+        let name: Ident = input
+            .parse()
+            .map_err(|err| Error::new(err.span(), "expected nametable name here"))?;
+
+        let base = if input.parse::<Option<Token![:]>>()?.is_some() {
+            let path = input
+                .call(Path::parse_mod_style)
+                .map_err(|err| Error::new(err.span(), "expected base nametable path here"))?;
+            Some(path)
+        } else {
+            None
+        };
+
+        let content;
+        braced!(content in input);
+        let entries: Vec<Entry> = content
+            .parse_terminated(Entry::parse, Token![,])?
+            .into_iter()
+            .collect();
+
+        check_not_empty(&name, &entries)?;
+        check_duplicate_strings(&name, &entries)?;
+        let options = take_options(&name, &mut attrs, &entries)?;
+
+        Ok(Table {
+            attrs,
+            name,
+            base,
+            entries,
+            offset: options.offset,
+            payload_type: options.payload_type,
+            expected_initial: None,
+        })
+    }
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let key: Ident = input.parse()?;
+        let mut entry = Entry::parse_string(input, attrs, key)?;
+        if input.parse::<Option<Token![=>]>>()?.is_some() {
+            entry.payload = Some(input.parse()?);
+        }
+        Ok(entry)
+    }
+}
+
+impl Entry {
+    fn parse_string(input: ParseStream, attrs: Vec<Attribute>, key: Ident) -> Result<Self> {
+        if input.parse::<Option<Token![for]>>()?.is_some() {
+            // `LitStr::value` resolves escapes and raw strings, so the table
+            // holds the characters the literal stands for, not its source form.
+            let lit = match input.parse::<Lit>() {
+                Ok(Lit::Str(lit)) => lit,
+                Ok(Lit::ByteStr(lit)) => {
+                    return Err(Error::new(
+                        lit.span(),
+                        "expected string literal here, byte strings are not supported",
+                    ))
+                }
+                Ok(lit) => return Err(Error::new(lit.span(), "expected string literal here")),
+                Err(err) => return Err(Error::new(err.span(), "expected string literal here")),
+            };
+            if !lit.suffix().is_empty() {
+                return Err(Error::new(
+                    lit.span(),
+                    "string literal suffixes are not supported here",
+                ));
+            }
+            Ok(Entry {
+                attrs,
+                value: lit.value(),
+                value_span: lit.span(),
+                key,
+                payload: None,
+            })
+        } else {
+            Ok(Entry {
+                attrs,
+                value: key.unraw().to_string(),
+                value_span: key.span(),
+                key,
+                payload: None,
+            })
+        }
+    }
+}
+
+/// `Names` is a `#[repr(usize)]` enum, which cannot be empty.
+pub fn check_not_empty(name: &Ident, entries: &[Entry]) -> Result<()> {
+    if entries.is_empty() {
+        return Err(Error::new(
+            name.span(),
+            format!("nametable `{}` needs at least one entry", name),
+        ));
+    }
+    Ok(())
+}
+
+/// Every string must belong to exactly one entry, otherwise a lookup by string
+/// could not tell which index to return.
+pub fn check_duplicate_strings(name: &Ident, entries: &[Entry]) -> Result<()> {
+    let mut owners: HashMap<&str, &Ident> = HashMap::new();
+    let mut errors: Option<Error> = None;
+    for entry in entries {
+        if let Some(owner) = owners.get(entry.value.as_str()) {
+            let error = Error::new(
+                entry.value_span,
+                format!(
+                    "string {:?} of `{}` is already used by `{}` in nametable `{}`",
+                    entry.value, entry.key, owner, name
+                ),
+            );
+            match errors {
+                Some(ref mut errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        } else {
+            owners.insert(&entry.value, &entry.key);
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
+
+/// The offset types `#[nametable(offset = ...)]` accepts, with their maximum.
+const OFFSET_TYPES: &[(&str, u64)] = &[
+    ("u8", u8::MAX as u64),
+    ("u16", u16::MAX as u64),
+    ("u32", u32::MAX as u64),
+    ("usize", u64::MAX),
+];
+
+/// The options given in `#[nametable(...)]` attributes.
+pub struct TableOptions {
+    pub offset: Option<Ident>,
+    pub payload_type: Option<Type>,
+}
+
+/// Removes the `#[nametable(offset = u32, value = Type)]` attributes from
+/// `attrs` and checks the options they give against the entries.
+pub fn take_options(
+    name: &Ident,
+    attrs: &mut Vec<Attribute>,
+    entries: &[Entry],
+) -> Result<TableOptions> {
+    let mut offset = None;
+    let mut payload_type = None;
+    let mut result = Ok(());
+    attrs.retain(|attr| {
+        if !attr.path().is_ident("nametable") {
+            return true;
+        }
+        if result.is_ok() {
+            result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("offset") {
+                    offset = Some(meta.value()?.parse::<Ident>()?);
+                    Ok(())
+                } else if meta.path.is_ident("value") {
+                    payload_type = Some(meta.value()?.parse::<Type>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown nametable option, expected `offset` or `value`"))
+                }
+            });
+        }
+        false
+    });
+    result?;
+
+    if let Some(ref offset) = offset {
+        check_offset_type(name, offset, entries)?;
+    }
+    check_payloads(name, payload_type.is_some(), entries)?;
+    Ok(TableOptions {
+        offset,
+        payload_type,
+    })
+}
+
+/// The offset type must be able to hold every offset into the names blob and
+/// every local index.
+fn check_offset_type(name: &Ident, offset: &Ident, entries: &[Entry]) -> Result<()> {
+    let max = match OFFSET_TYPES.iter().find(|&&(ty, _)| offset == ty) {
+        Some(&(_, max)) => max,
+        None => {
+            return Err(Error::new(
+                offset.span(),
+                "expected one of `u8`, `u16`, `u32` or `usize` as the offset type",
+            ))
+        }
+    };
+    let names_len: usize = entries.iter().map(|entry| entry.value.len()).sum();
+    let needed = names_len.max(entries.len().saturating_sub(1)) as u64;
+    if needed > max {
+        return Err(Error::new(
+            offset.span(),
+            format!(
+                "nametable `{}` needs offsets up to {}, which do not fit in `{}`",
+                name, needed, offset
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// `VALUES` holds one payload per entry, so either every entry has one and the
+/// table declares their type, or no entry has one.
+fn check_payloads(name: &Ident, has_type: bool, entries: &[Entry]) -> Result<()> {
+    let mut errors: Option<Error> = None;
+    for entry in entries {
+        let error = match (has_type, entry.payload.is_some()) {
+            (true, false) => Error::new(
+                entry.key.span(),
+                format!(
+                    "entry `{}` needs a value after `=>`, since nametable `{}` declares a value type",
+                    entry.key, name
+                ),
+            ),
+            (false, true) => Error::new(
+                entry.key.span(),
+                format!(
+                    "entry `{}` has a value, but nametable `{}` declares no value type; \
+                     add `#[nametable(value = Type)]` to the table",
+                    entry.key, name
+                ),
+            ),
+            _ => continue,
+        };
+        match errors {
+            Some(ref mut errors) => errors.combine(error),
+            None => errors = Some(error),
+        }
+    }
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashSet;
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use nametable::NameTable;
+use proc_macro2::{Span, TokenStream};
+use syn::parse::Parser;
+use syn::{Attribute, Ident, Path};
+
+use crate::generate::generate_nametable_item;
+use crate::parse::{check_duplicate_strings, check_not_empty, take_options, Entry, Table};
+
+/// A table definition assembled in code, for generating tables from build
+/// scripts without going through the `nametable!` macro.
+///
+/// ```ignore
+/// let spec = TableSpec::new("keywords")
+///     .entry("Fn", "fn")
+///     .entry("Let", "let");
+/// nametable_codegen::write_to_out_dir("keywords.rs", &[spec]).unwrap();
+/// ```
+///
+/// The result is the same module `nametable!` would produce for
+/// `nametable keywords { Fn for "fn", Let for "let" }`.
+#[derive(Clone, Debug)]
+pub struct TableSpec {
+    attrs: Vec<String>,
+    name: String,
+    base: Option<String>,
+    entries: Vec<(String, String)>,
+    initial: Option<usize>,
+    pub(crate) source_path: Option<PathBuf>,
+}
+
+impl TableSpec {
+    pub fn new(name: &str) -> Self {
+        TableSpec {
+            attrs: Vec::new(),
+            name: name.to_owned(),
+            base: None,
+            entries: Vec::new(),
+            initial: None,
+            source_path: None,
+        }
+    }
+
+    /// Layers the table upon `path`, written as it would be after `:` in
+    /// `nametable!`, relative to the module the generated source ends up in.
+    pub fn base(mut self, path: &str) -> Self {
+        self.base = Some(path.to_owned());
+        self
+    }
+
+    /// Adds attributes, written as in `nametable!` (for example
+    /// `#[derive(Debug)]`), to the table.
+    pub fn attribute(mut self, attr: &str) -> Self {
+        self.attrs.push(attr.to_owned());
+        self
+    }
+
+    /// Adds an entry whose string is `value` and whose variant is `key`.
+    pub fn entry(mut self, key: &str, value: &str) -> Self {
+        self.entries.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Adds an entry that stands for its own name.
+    pub fn name(self, key: &str) -> Self {
+        self.entry(key, key)
+    }
+
+    /// Builds a spec holding the local names of `table`, in index order, for
+    /// checking a table collected at run time into source control.
+    ///
+    /// Variant names are derived from the strings: runs of ASCII letters and
+    /// digits become capitalized words (`"hello_world"` becomes `HelloWorld`),
+    /// and a derived name that is empty, clashes with an earlier one or is not
+    /// an identifier gets a numeric suffix. A layer with a parent also needs a
+    /// `base` naming the module generated for that parent: the generated
+    /// module fails to compile unless its `INITIAL` matches the layer's
+    /// `initial_local()`, and generating it without a base is an error.
+    pub fn from_layer<T: NameTable + ?Sized>(name: &str, table: &T) -> Self {
+        let mut spec = TableSpec::new(name);
+        spec.initial = Some(table.initial_local());
+        let mut used = HashSet::new();
+        for idx in 0..table.len_local() {
+            let value = table.at_local(idx);
+            let key = variant_name(value, &mut used);
+            spec = spec.entry(&key, value);
+        }
+        spec
+    }
+
+    pub fn to_tokens(&self) -> Result<TokenStream, Error> {
+        Ok(generate_nametable_item(&self.to_table()?))
+    }
+
+    pub fn to_source(&self) -> Result<String, Error> {
+        generate_source(std::slice::from_ref(self))
+    }
+
+    fn to_table(&self) -> Result<Table, Error> {
+        let name = parse_ident(&self.name)
+            .map_err(|_| Error::Invalid(format!("invalid nametable name `{}`", self.name)))?;
+        let base = match self.base {
+            Some(ref base) => Some(Path::parse_mod_style.parse_str(base).map_err(|_| {
+                Error::Invalid(format!("invalid base path `{}` for nametable `{}`", base, self.name))
+            })?),
+            None => None,
+        };
+        let mut entries = Vec::new();
+        for (key, value) in self.entries.iter() {
+            let key = parse_ident(key).map_err(|_| {
+                Error::Invalid(format!("invalid entry name `{}` in nametable `{}`", key, self.name))
+            })?;
+            entries.push(Entry {
+                attrs: Vec::new(),
+                key,
+                value: value.clone(),
+                value_span: Span::call_site(),
+                payload: None,
+            });
+        }
+        check_not_empty(&name, &entries).map_err(|err| Error::Invalid(err.to_string()))?;
+        check_duplicate_strings(&name, &entries).map_err(|err| Error::Invalid(err.to_string()))?;
+        // Without a base the table starts at 0, so a layer that starts
+        // anywhere else cannot be reproduced.
+        let expected_initial = match (self.initial, &base) {
+            (Some(initial), None) if initial != 0 => {
+                return Err(Error::Invalid(format!(
+                    "nametable `{}` was built from a layer starting at index {}, but has no base",
+                    self.name, initial
+                )))
+            }
+            (Some(initial), Some(_)) => Some(initial),
+            _ => None,
+        };
+        let mut attrs = Attribute::parse_outer
+            .parse_str(&self.attrs.join("\n"))
+            .map_err(|_| {
+                Error::Invalid(format!("invalid attributes for nametable `{}`", self.name))
+            })?;
+        let options = take_options(&name, &mut attrs, &entries)
+            .map_err(|err| Error::Invalid(err.to_string()))?;
+        Ok(Table {
+            attrs,
+            name,
+            base,
+            entries,
+            offset: options.offset,
+            payload_type: options.payload_type,
+            expected_initial,
+        })
+    }
+}
+
+fn parse_ident(ident: &str) -> syn::Result<Ident> {
+    syn::parse_str::<Ident>(ident)
+}
+
+/// Names the generated `Names` enum already uses for its own items; a variant
+/// named after one would shadow it.
+const RESERVED_VARIANT_NAMES: &[&str] = &["ALL"];
+
+pub(crate) fn variant_name(value: &str, used: &mut HashSet<String>) -> String {
+    let mut key = String::new();
+    for word in value.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            key.push(first.to_ascii_uppercase());
+            key.extend(chars);
+        }
+    }
+    if key.is_empty() {
+        key.push_str("Name");
+    } else if key.starts_with(|c: char| c.is_ascii_digit()) {
+        key.insert(0, '_');
+    }
+    let mut candidate = key.clone();
+    let mut suffix = 1;
+    while used.contains(&candidate)
+        || RESERVED_VARIANT_NAMES.contains(&candidate.as_str())
+        || parse_ident(&candidate).is_err()
+    {
+        suffix += 1;
+        candidate = format!("{}{}", key, suffix);
+    }
+    used.insert(candidate.clone());
+    candidate
+}
+
+/// Generates formatted Rust source for `specs`, in order.
+pub fn generate_source(specs: &[TableSpec]) -> Result<String, Error> {
+    let mut tokens = TokenStream::new();
+    for spec in specs {
+        tokens.extend(spec.to_tokens()?);
+    }
+    let file: syn::File = syn::parse2(tokens).map_err(|err| Error::Invalid(err.to_string()))?;
+    Ok(prettyplease::unparse(&file))
+}
+
+/// Writes the source for `specs` to `$OUT_DIR/file_name`, for use with
+/// `include!(concat!(env!("OUT_DIR"), "/file_name"))`. Returns the full path.
+///
+/// Specs loaded with `TableSpec::from_file` make cargo rerun the build script
+/// whenever their file changes.
+pub fn write_to_out_dir(file_name: &str, specs: &[TableSpec]) -> Result<PathBuf, Error> {
+    let out_dir = env::var_os("OUT_DIR")
+        .ok_or_else(|| Error::Invalid("OUT_DIR is not set; call this from a build script".to_owned()))?;
+    let path = PathBuf::from(out_dir).join(file_name);
+    let source = generate_source(specs)?;
+    fs::write(&path, source).map_err(Error::Io)?;
+    for spec in specs {
+        if let Some(ref source_path) = spec.source_path {
+            println!("cargo:rerun-if-changed={}", source_path.display());
+        }
+    }
+    Ok(path)
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The table definition cannot be turned into a module.
+    Invalid(String),
+    /// A data file passed to `TableSpec::from_file` cannot be read or parsed.
+    File {
+        path: PathBuf,
+        line: Option<usize>,
+        message: String,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Invalid(ref message) => write!(f, "nametable_codegen: {}", message),
+            Error::File {
+                ref path,
+                line: Some(line),
+                ref message,
+            } => write!(
+                f,
+                "nametable_codegen: {}:{}: {}",
+                path.display(),
+                line,
+                message
+            ),
+            Error::File {
+                ref path,
+                line: None,
+                ref message,
+            } => write!(f, "nametable_codegen: {}: {}", path.display(), message),
+            Error::Io(ref err) => write!(f, "nametable_codegen: {}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Invalid(_) | Error::File { .. } => None,
+            Error::Io(ref err) => Some(err),
+        }
+    }
+}
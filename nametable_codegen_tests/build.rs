@@ -1,15 +1,25 @@
-extern crate syntex;
-extern crate nametable_codegen;
-
-use std::env;
-use std::path::Path;
+use nametable::DynamicNameTable;
+use nametable_codegen::{write_to_out_dir, TableSpec};
 
 fn main() {
-    let mut registry = syntex::Registry::new();
-    nametable_codegen::register(&mut registry);
+    let spec = TableSpec::new("built_table")
+        .attribute("#[derive(Debug, PartialEq)]")
+        .base("super::simple_table3")
+        .name("I")
+        .entry("J", "Hello World")
+        .entry("K", "k");
+
+    write_to_out_dir("tables.rs", &[spec]).unwrap();
 
-    let src = Path::new("tests/tables.in.rs");
-    let dst = Path::new(&env::var("OUT_DIR").unwrap()).join("tables.rs");
+    let keywords = TableSpec::from_file("tables/keywords.txt").unwrap();
+    let operators = TableSpec::from_file("tables/operators.toml").unwrap();
+    let errors = TableSpec::from_file("tables/error_codes.json").unwrap();
+    write_to_out_dir("file_tables.rs", &[keywords, operators, errors]).unwrap();
 
-    registry.expand("nametable_codegen_test", &src, &dst).unwrap();
+    let mut scanned = DynamicNameTable::new();
+    for word in &["fn", "self", "hello_world", "fooBar", "+", "-", "9lives", "Fn", "fn", "ALL"] {
+        scanned.intern(word);
+    }
+    let scanned_table = TableSpec::from_layer("scanned", &scanned);
+    write_to_out_dir("scanned_tables.rs", &[scanned_table]).unwrap();
 }
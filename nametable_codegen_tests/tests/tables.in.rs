@@ -1,23 +0,0 @@
-
-nametable! {
-    nametable simple_table {
-        A,
-        B,
-        C for "Hello",
-        D
-    }
-
-    nametable simple_table2 {
-        A,
-        B,
-        C for "Hello",
-        D for "Hello"
-    }
-
-    nametable simple_table3 : simple_table {
-        E,
-        F for "EF",
-        G for "G"
-    }
-
-    }
@@ -1,6 +1,474 @@
-extern crate nametable;
+use nametable::NameTable;
+use nametable_macros::nametable;
 
-include!(concat!(env!("OUT_DIR"), "/tables.rs"));
+nametable! {
+    nametable simple_table {
+        A,
+        B,
+        C for "Hello",
+        D
+    }
+
+    nametable simple_table2 {
+        A,
+        B,
+        C for "Hello",
+        D for "World"
+    }
+
+    nametable simple_table3 : simple_table {
+        E,
+        F for "EF",
+        G for "G"
+    }
+}
+
+nametable! {
+    nametable escaped_table {
+        Newline for "\n",
+        Accent for "\u{e9}",
+        Quote for "\"",
+        Backslash for "\\",
+        Raw for r"C:\path\n",
+        RawHashed for r#"say "hi""#,
+        Continued for "a\
+                       b",
+        r#type,
+    }
+}
+
+nametable! {
+    /// Names with the derives needed to use them as map keys.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+    nametable derived_table {
+        /// The first entry.
+        First,
+        #[deprecated(note = "use `First`")]
+        Old for "old",
+        Last,
+    }
+}
+
+nametable! {
+    /// Offsets and indices stored as `u16`.
+    #[nametable(offset = u16)]
+    #[derive(Debug, PartialEq)]
+    nametable compact_table : simple_table3 {
+        X,
+        Y for "Hello Compact",
+        Z,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Opcode {
+    arity: usize,
+}
+
+const BINARY: usize = 2;
+
+nametable! {
+    #[nametable(value = Opcode)]
+    nametable opcodes {
+        Add for "add" => Opcode { arity: BINARY },
+        Neg for "neg" => Opcode { arity: 1 },
+        Nop => Opcode { arity: 0 },
+    }
+
+    #[nametable(offset = u8, value = &'static str)]
+    nametable opcode_aliases : opcodes {
+        Plus for "+" => "add",
+        Minus for "-" => "neg",
+    }
+}
+
+nametable! {
+    nametable sql_keywords {
+        SELECT,
+        FROM,
+        ALL,
+    }
+
+    nametable method_names {
+        from_index,
+        as_str,
+    }
+}
+
+mod nested {
+    use nametable_macros::nametable;
+
+    nametable! {
+        nametable nested_table : super::simple_table3 {
+            H,
+        }
+    }
+}
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/tables.rs"));
+}
+
+mod file_generated {
+    include!(concat!(env!("OUT_DIR"), "/file_tables.rs"));
+}
+
+mod scanned_generated {
+    include!(concat!(env!("OUT_DIR"), "/scanned_tables.rs"));
+}
 
 #[test]
 fn it_works() {}
+
+#[test]
+fn test_layout() {
+    assert_eq!(simple_table::INITIAL, 0);
+    assert_eq!(simple_table::COUNT, 4);
+    assert_eq!(simple_table3::INITIAL, 4);
+    assert_eq!(simple_table3::COUNT, 3);
+    assert_eq!(nested::nested_table::INITIAL, 7);
+    assert_eq!(nested::nested_table::COUNT, 1);
+
+    assert_eq!(simple_table::Names::C as usize, 2);
+    assert_eq!(simple_table3::Names::F as usize, 5);
+    assert_eq!(nested::nested_table::Names::H as usize, 7);
+}
+
+#[test]
+fn test_constructors() {
+    let tbl = simple_table3::new();
+    assert!(tbl.len() == 7);
+    assert!(&tbl[simple_table::Names::C] == "Hello");
+    assert!(&tbl[simple_table3::Names::F] == "EF");
+    assert!(tbl.find("Hello").unwrap() == simple_table::Names::C as usize);
+    assert!(tbl.find("D").unwrap() == 3);
+    assert!(tbl.find("G").unwrap() == 6);
+    assert!(tbl.find("F").is_none());
+
+    let tbl = simple_table3::new_plain();
+    assert!(tbl.len() == 7);
+    assert!(tbl.find("EF").unwrap() == 5);
+
+    let mut tbl = simple_table3::new_dynamic();
+    assert!(tbl.intern("Hello") == 2);
+    assert!(tbl.intern("World") == 7);
+
+    let mut tbl = simple_table2::new_dynamic_plain();
+    assert!(tbl.intern("World") == 3);
+    assert!(tbl.intern("Hello") == 2);
+    assert!(tbl.intern("E") == 4);
+
+    let tbl = nested::nested_table::new();
+    assert!(tbl.len() == 8);
+    assert!(tbl.at(7) == "H");
+    assert!(tbl.find("A").unwrap() == 0);
+
+    let shared = simple_table3::shared();
+    assert!(std::ptr::eq(shared, simple_table3::shared()));
+    assert!(shared.len() == 7);
+    let tbl = simple_table3::new();
+    assert!(std::ptr::addr_eq(tbl.parent().unwrap(), simple_table::shared()));
+    let tbl = simple_table3::new_plain();
+    assert!(std::ptr::addr_eq(tbl.parent().unwrap(), &simple_table::SHARED_PLAIN));
+
+    let workers: Vec<_> = (0..4)
+        .map(|worker| {
+            std::thread::spawn(move || {
+                let mut tbl = simple_table3::new_dynamic();
+                tbl.intern(&format!("worker{}", worker))
+            })
+        })
+        .collect();
+    for handle in workers {
+        assert!(handle.join().unwrap() == 7);
+    }
+}
+
+#[test]
+fn test_table_spec() {
+    assert_eq!(generated::built_table::INITIAL, 7);
+    assert_eq!(generated::built_table::COUNT, 3);
+
+    let tbl = generated::built_table::new();
+    assert!(tbl.len() == 10);
+    assert!(&tbl[generated::built_table::Names::J] == "Hello World");
+    assert_eq!(generated::built_table::Names::I, generated::built_table::Names::I);
+    assert!(tbl.find("k").unwrap() == 9);
+    assert!(tbl.find("EF").unwrap() == 5);
+
+    let source = nametable_codegen::TableSpec::new("spec_table")
+        .name("A")
+        .entry("B", "b")
+        .to_source()
+        .unwrap();
+    assert!(source.contains("pub mod spec_table"));
+    assert!(source.contains("\"Ab\""));
+
+    assert!(nametable_codegen::TableSpec::new("1table").to_source().is_err());
+    assert!(nametable_codegen::TableSpec::new("table").to_source().is_err());
+    assert!(nametable_codegen::TableSpec::new("table").name("fn").to_source().is_err());
+    assert!(nametable_codegen::TableSpec::new("table").base("not a path").to_source().is_err());
+    assert!(nametable_codegen::TableSpec::new("table")
+        .entry("A", "x")
+        .entry("B", "x")
+        .to_source()
+        .is_err());
+}
+
+fn load_error(file_name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("nametable_codegen_tests_{}", file_name));
+    std::fs::write(&path, contents).unwrap();
+    let result = nametable_codegen::TableSpec::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap_err().to_string()
+}
+
+fn load_source(file_name: &str, contents: &str) -> String {
+    let path = std::env::temp_dir().join(format!("nametable_codegen_tests_{}", file_name));
+    std::fs::write(&path, contents).unwrap();
+    let result = nametable_codegen::TableSpec::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap().to_source().unwrap()
+}
+
+#[test]
+fn test_data_files() {
+    assert_eq!(file_generated::keywords::COUNT, 3);
+    assert_eq!(file_generated::operators::INITIAL, 3);
+    assert_eq!(file_generated::errors::INITIAL, 6);
+
+    let tbl = file_generated::errors::new();
+    assert!(tbl.len() == 8);
+    assert!(&tbl[file_generated::keywords::Names::While] == "while");
+    assert!(&tbl[file_generated::operators::Names::Add] == "+");
+    assert!(&tbl[file_generated::operators::Names::Not] == "Not");
+    assert!(&tbl[file_generated::errors::Names::E0002] == "unknown name");
+    assert!(tbl.find("-").unwrap() == 4);
+
+    let error = load_error("bad.txt", "A\nB\n\nA\n");
+    assert!(error.ends_with("bad.txt:4: string \"A\" is already used by `A`"), "{}", error);
+
+    let source = load_source("names.txt", "fn\nmatch\nE-0001\nnot valid\n# comment\n\\#include\n");
+    assert!(source.contains("Fn = INITIAL,"), "{}", source);
+    assert!(source.contains("Match = INITIAL + 1,"), "{}", source);
+    assert!(source.contains("E0001 = INITIAL + 2,"), "{}", source);
+    assert!(source.contains("NotValid = INITIAL + 3,"), "{}", source);
+    assert!(source.contains("Include = INITIAL + 4,"), "{}", source);
+    assert!(source.contains("not valid#include\""), "{}", source);
+
+    let error = load_error("bad.toml", "[[entries]]\nkey = \"A\"\n\n[[entries]]\nkey = \"B\"\nvalue = \"A\"\n");
+    assert!(error.contains("bad.toml:4: "), "{}", error);
+    assert!(error.contains("already used by `A`"), "{}", error);
+    let error = load_error("syntax.toml", "base = \"x\"\nentries = [\n");
+    assert!(error.contains("syntax.toml:"), "{}", error);
+
+    let error = load_error("bad.json", "{\n  \"entries\": [\n    { \"key\": \"A\" },\n    { \"key\": \"fn\" }\n  ]\n}\n");
+    assert!(error.ends_with("bad.json:4: `fn` is not a valid entry name"), "{}", error);
+    let error = load_error("unknown.json", "{ \"entries\": [], \"extra\": 1 }");
+    assert!(error.contains("unknown.json:1: unknown field `extra`"), "{}", error);
+}
+
+#[test]
+fn test_string_literals() {
+    use escaped_table::Names;
+
+    let tbl = escaped_table::new();
+    assert!(&tbl[Names::Newline] == "\n");
+    assert!(&tbl[Names::Accent] == "\u{e9}");
+    assert!(&tbl[Names::Quote] == "\"");
+    assert!(&tbl[Names::Backslash] == "\\");
+    assert!(&tbl[Names::Raw] == "C:\\path\\n");
+    assert!(&tbl[Names::RawHashed] == "say \"hi\"");
+    assert!(&tbl[Names::Continued] == "ab");
+    assert!(&tbl[Names::r#type] == "type");
+
+    assert!(tbl.find("\u{e9}").unwrap() == Names::Accent as usize);
+    assert!(tbl.find("say \"hi\"").unwrap() == Names::RawHashed as usize);
+    assert!(tbl.find("type").unwrap() == Names::r#type as usize);
+    assert!(escaped_table::new_plain().find("ab").unwrap() == Names::Continued as usize);
+}
+
+#[test]
+fn test_attributes() {
+    use derived_table::Names;
+    use std::collections::BTreeMap;
+    use std::collections::HashSet;
+
+    assert!(Names::First == Names::First);
+    assert!(Names::First != Names::Last);
+    assert!(Names::First < Names::Last);
+    assert!(format!("{:?}", Names::Last) == "Last");
+
+    let mut set = HashSet::new();
+    set.insert(Names::First);
+    set.insert(Names::First);
+    assert!(set.len() == 1);
+
+    let mut map = BTreeMap::new();
+    map.insert(Names::Last, 2);
+    map.insert(Names::First, 1);
+    assert!(map.keys().cloned().collect::<Vec<_>>() == vec![Names::First, Names::Last]);
+
+    #[allow(deprecated)]
+    let old = Names::Old;
+    assert!(&derived_table::new()[old] == "old");
+}
+
+#[test]
+fn test_names_conversions() {
+    use std::convert::TryFrom;
+
+    assert!(simple_table::Names::C.as_str() == "Hello");
+    assert!(simple_table3::Names::F.as_str() == "EF");
+    assert!(escaped_table::Names::Accent.as_str() == "\u{e9}");
+
+    assert!("Hello".parse::<simple_table::Names>().unwrap() as usize == 2);
+    assert!("EF".parse::<simple_table3::Names>().unwrap() as usize == 5);
+    assert!("D".parse::<simple_table::Names>().unwrap() as usize == 3);
+    assert!("EF".parse::<simple_table::Names>().err() == Some(nametable::UnknownName));
+    assert!("Hello".parse::<simple_table3::Names>().is_err());
+    assert!("".parse::<simple_table3::Names>().is_err());
+
+    assert!(simple_table3::Names::from_index(4).unwrap() as usize == 4);
+    assert!(simple_table3::Names::from_index(6).unwrap().as_str() == "G");
+    assert!(simple_table3::Names::from_index(3).is_none());
+    assert!(simple_table3::Names::from_index(7).is_none());
+    assert!(simple_table3::Names::from_index(usize::MAX).is_none());
+
+    assert!(simple_table3::Names::try_from(5).unwrap().as_str() == "EF");
+    let err = simple_table3::Names::try_from(0).err().unwrap();
+    assert!(err == nametable::IndexOutOfRange { index: 0, initial: 4, count: 3 });
+    assert!(err.to_string() == "index 0 is outside the table's range 4..7");
+
+    assert!(method_names::Names::try_from(0).unwrap().as_str() == "from_index");
+    assert!("as_str".parse::<method_names::Names>().unwrap() as usize == 1);
+}
+
+#[test]
+fn test_all_names() {
+    assert!(simple_table3::Names::ALL.len() == simple_table3::COUNT);
+    assert!(simple_table3::Names::ALL.iter().map(|name| name.as_str()).collect::<Vec<_>>() ==
+            vec!["E", "EF", "G"]);
+    assert!(simple_table::iter().map(|name| name as usize).collect::<Vec<_>>() == vec![0, 1, 2, 3]);
+
+    let tbl = simple_table3::new();
+    for name in simple_table3::iter() {
+        assert!(tbl.find(name.as_str()).unwrap() == name as usize);
+    }
+    assert!(escaped_table::iter().count() == escaped_table::COUNT);
+
+    assert!(sql_keywords::iter().map(|name| name.as_str()).collect::<Vec<_>>() ==
+            vec!["SELECT", "FROM", "ALL"]);
+    assert!("ALL".parse::<sql_keywords::Names>().unwrap() as usize == 2);
+    assert!(sql_keywords::Names::from_index(2).unwrap().as_str() == "ALL");
+}
+
+#[test]
+fn test_from_layer() {
+    use scanned_generated::scanned::{self, Names};
+
+    assert_eq!(scanned::COUNT, 9);
+    assert_eq!(Names::Fn.as_str(), "fn");
+    assert_eq!(Names::Self2.as_str(), "self");
+    assert_eq!(Names::HelloWorld.as_str(), "hello_world");
+    assert_eq!(Names::FooBar.as_str(), "fooBar");
+    assert_eq!(Names::Name.as_str(), "+");
+    assert_eq!(Names::Name2.as_str(), "-");
+    assert_eq!(Names::_9lives.as_str(), "9lives");
+    assert_eq!(Names::Fn2.as_str(), "Fn");
+    assert_eq!(Names::ALL2.as_str(), "ALL");
+    assert_eq!(Names::ALL.len(), scanned::COUNT);
+    assert!(scanned::new().find("hello_world") == Some(Names::HelloWorld as usize));
+
+    let mut tbl = nametable::DynamicNameTable::new_upon(simple_table::new());
+    tbl.intern("extra name");
+    let source = nametable_codegen::TableSpec::from_layer("extra", &tbl)
+        .base("simple_table")
+        .to_source()
+        .unwrap();
+    assert!(source.contains("pub mod extra"));
+    assert!(source.contains("ExtraName = INITIAL"));
+    assert!(source.contains("super::simple_table::INITIAL + super::simple_table::COUNT"));
+    assert!(source.contains(&format!("INITIAL == {},", simple_table::COUNT)));
+
+    let err = nametable_codegen::TableSpec::from_layer("extra", &tbl)
+        .to_source()
+        .unwrap_err();
+    assert!(err.to_string().contains("has no base"));
+
+    let empty = nametable::DynamicNameTable::new_upon(simple_table::new());
+    assert!(nametable_codegen::TableSpec::from_layer("empty", &empty)
+        .base("simple_table")
+        .to_source()
+        .is_err());
+}
+
+#[test]
+fn test_offset_types() {
+    use compact_table::Names;
+
+    assert_eq!(compact_table::INITIAL, 7);
+    let tbl: nametable::StaticHashedNameTable<u16, dyn nametable::NameTable + Send + Sync> =
+        compact_table::new();
+    assert!(tbl.len() == 10);
+    assert!(&tbl[Names::Y] == "Hello Compact");
+    assert!(tbl.find("Z") == Some(Names::Z as usize));
+    assert!(tbl.find("EF") == Some(5));
+    assert!(compact_table::new_plain().find("X") == Some(Names::X as usize));
+    assert_eq!(Names::Y.as_str(), "Hello Compact");
+    assert_eq!("Z".parse::<Names>().unwrap(), Names::Z);
+
+    let source = nametable_codegen::TableSpec::new("spec_table")
+        .attribute("#[nametable(offset = u8)]")
+        .name("A")
+        .to_source()
+        .unwrap();
+    assert!(source.contains("const INDEX_DATA: &[u8]"));
+    assert!(!source.contains("#[nametable"));
+
+    let long_name = "x".repeat(256);
+    let err = nametable_codegen::TableSpec::new("long_table")
+        .attribute("#[nametable(offset = u8)]")
+        .entry("A", &long_name)
+        .to_source()
+        .unwrap_err();
+    assert!(err.to_string().contains("needs offsets up to 256, which do not fit in `u8`"), "{}", err);
+    assert!(nametable_codegen::TableSpec::new("table")
+        .attribute("#[nametable(offset = i32)]")
+        .to_source()
+        .is_err());
+    assert!(nametable_codegen::TableSpec::new("table")
+        .attribute("#[nametable(width = u32)]")
+        .to_source()
+        .is_err());
+}
+
+#[test]
+fn test_values() {
+    use nametable::NameMap;
+
+    assert!(opcodes::VALUES.len() == opcodes::COUNT);
+    assert_eq!(opcodes::Names::Add.value(), &Opcode { arity: 2 });
+    assert_eq!(opcodes::Names::Nop.value().arity, 0);
+    assert_eq!(*opcode_aliases::Names::Minus.value(), "neg");
+    assert_eq!(opcode_aliases::VALUES, ["add", "neg"]);
+
+    let tbl = opcode_aliases::new();
+    let mut arities = NameMap::for_table(&tbl);
+    arities.extend(opcodes::iter().map(|name| (name, name.value().arity)));
+    for alias in opcode_aliases::iter() {
+        let target = tbl.find(alias.value()).unwrap();
+        let arity = arities[target];
+        arities.insert(alias, arity);
+    }
+    assert!(arities.len() == 5);
+    assert!(arities.get_by_name(&tbl, "-") == Some(&1));
+    assert!(arities[opcode_aliases::Names::Plus] == 2);
+
+    let err = nametable_codegen::TableSpec::new("table")
+        .attribute("#[nametable(value = u8)]")
+        .name("A")
+        .to_source()
+        .unwrap_err();
+    assert!(err.to_string().contains("entry `A` needs a value after `=>`"), "{}", err);
+}
@@ -0,0 +1,70 @@
+//! The `nametable!` procedural macro.
+
+extern crate nametable_codegen;
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+
+/// Declares one or more name tables.
+///
+/// ```ignore
+/// nametable! {
+///     nametable keywords {
+///         Fn for "fn",
+///         Let for "let",
+///         Match for "match"
+///     }
+///
+///     nametable attributes : keywords {
+///         Inline for "inline",
+///         Cold for "cold"
+///     }
+/// }
+/// ```
+///
+/// Each table becomes a module with the same name, holding `INITIAL` and
+/// `COUNT`, a `Names` enum whose variants are the table's indices, and the
+/// constructors `new`, `new_dynamic`, `new_plain` and `new_dynamic_plain`. An
+/// entry without `for "..."` stands for its own name. `shared()` returns one
+/// process-wide instance of `new()`, kept in a `static`; tables layered upon a
+/// table, and its `new_dynamic` tables, all reuse that instance as their
+/// parent. `new_plain` tables likewise sit on a `static` plain instance of the
+/// base, so a plain chain stays plain. The static tables keep their parent as
+/// `dyn NameTable + Send + Sync`, so `new()` returns a
+/// `StaticHashedNameTable<usize, dyn NameTable + Send + Sync>`. Everything but
+/// `new_dynamic` and `new_dynamic_plain` works without `nametable`'s `alloc`
+/// feature.
+///
+/// `Names` converts to and from strings and indices without a table:
+/// `Names::as_str`, `Names::from_index`, and the `FromStr` and `TryFrom<usize>`
+/// implementations all read the module's static data directly. `Names::ALL`
+/// and the module's `iter()` list every variant in declaration order; in a
+/// table with an entry named `ALL`, that entry takes the name, so use `iter()`.
+///
+/// Attributes may precede a table or an entry. Doc comments and `#[cfg]` on a
+/// table apply to its module, other table attributes such as
+/// `#[derive(Debug, PartialEq, Eq, Hash)]` apply to its `Names` enum, and entry
+/// attributes such as `#[doc]` or `#[deprecated]` apply to the entry's variant.
+/// `Names` is always `Copy` and `Clone`.
+///
+/// `#[nametable(offset = u32)]` on a table stores its name offsets and local
+/// indices as `u32` instead of `usize`, and its constructors return
+/// `StaticHashedNameTable<u32, _>` and `StaticNameTable<u32, _>`. `u8`, `u16`,
+/// `u32` and `usize` are accepted; a type too narrow for the table's names is an
+/// error. This shrinks the offsets and the perfect hash map.
+///
+/// `#[nametable(value = Type)]` gives every entry a value of `Type`, written
+/// after `=>`: `Add for "add" => Opcode { arity: 2 }`. The values must be
+/// constant expressions; they and `Type` are resolved from the module
+/// containing the macro invocation. The module then holds them in a `VALUES`
+/// static in declaration order, and `Names::value` returns an entry's value.
+/// To attach values at run time, or across layers, use `nametable::NameMap`.
+///
+/// A table declared with `: base` continues the indices of the base table and
+/// is layered on top of it. The base path is resolved from the module
+/// containing the macro invocation; name tables from other crates need a
+/// leading `::`.
+#[proc_macro]
+pub fn nametable(input: TokenStream) -> TokenStream {
+    nametable_codegen::expand(input.into()).into()
+}